@@ -0,0 +1,423 @@
+//! Network statistics, written against the [`Graph`] trait.
+//!
+//! [`Analysis`] is implemented for every [`Graph`], so the statistics are
+//! called as methods and work the same on a
+//! [`SimpleNetwork`](crate::SimpleNetwork), a [`CsrGraph`] or a view. Those
+//! that search from every node first copy the graph into CSR form, which is
+//! much cheaper to traverse.
+
+use std::cmp::max;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use rayon::prelude::*;
+
+use crate::assortativity::{average_neighbor_degrees, knn_spectrum, JointDegreeMatrix, KnnClass};
+use crate::bfs::BfsWorkspace;
+use crate::closeness::{top_k_closeness, Closeness};
+use crate::clustering::Clustering;
+use crate::csr::CsrGraph;
+use crate::degree::DegreeDistribution;
+use crate::distance::{ifub_diameter, Diameter};
+use crate::error::Result;
+use crate::graph::Graph;
+use crate::node::Node;
+use crate::spectral::{katz, leading_eigenvector, pagerank, Convergence};
+
+/// The connected components of a network.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Components<N: Node = u64> {
+    /// Component of each node. Components are numbered by decreasing size,
+    /// so the giant component is 0
+    pub labels: HashMap<N, usize>,
+    /// Size of each component, largest first
+    pub sizes: Vec<usize>,
+    /// The nodes of each component, sorted, in label order
+    members_by_label: Vec<Vec<N>>,
+}
+
+impl<N: Node> Components<N> {
+    pub fn count(&self) -> usize {
+        self.sizes.len()
+    }
+
+    /// Fraction of all nodes that are in the largest component
+    pub fn giant_fraction(&self) -> f64 {
+        match self.sizes.first() {
+            Some(&giant) => (giant as f64) / (self.labels.len() as f64),
+            None => 0.0,
+        }
+    }
+
+    /// The nodes of component `label`, sorted
+    pub fn members(&self, label: usize) -> &[N] {
+        &self.members_by_label[label]
+    }
+
+    /// The nodes of every component, sorted, indexed by label
+    pub fn members_by_label(&self) -> &[Vec<N>] {
+        &self.members_by_label
+    }
+}
+
+/// Statistics of any [`Graph`].
+pub trait Analysis: Graph {
+    /// Computes mean square-degree
+    fn mean_square_degree(&self) -> f64 {
+        let total: usize = self.nodes().map(|node| self.degree(node).pow(2)).sum();
+        (total as f64) / (self.num_nodes() as f64)
+    }
+
+    fn mean_degree_empirical(&self) -> f64 {
+        let total: usize = self.nodes().map(|node| self.degree(node)).sum();
+        (total as f64) / (self.num_nodes() as f64)
+    }
+
+    /// The degree sequence, with its CCDF and histograms
+    fn degree_distribution(&self) -> DegreeDistribution {
+        DegreeDistribution::new(self.nodes().map(|node| self.degree(node)).collect())
+    }
+
+    /// SSSP using BFS for simple graphs
+    ///
+    /// Starting from a given vertex, builds a `HashMap` keyed by vertex
+    /// storing the distance from source to that vertex. If a vertex is
+    /// unreachable, it is not in the map.
+    ///
+    /// Should take O(V+E) time
+    fn sssp(&self, from: Self::Node) -> HashMap<Self::Node, i64> {
+        // Map to return, and FIFO queue for next vertices to check - with
+        // unit edge lengths vertices come off it in order of distance
+        let mut dists = HashMap::new();
+        let mut queue = VecDeque::new();
+        dists.insert(from, 0);
+        queue.push_back(from);
+        while let Some(next) = queue.pop_front() {
+            // The distances are all +1 from previous vertex
+            let base_dist = dists[&next];
+            for node in self.neighbors(next) {
+                if let Entry::Vacant(entry) = dists.entry(node) {
+                    entry.insert(base_dist + 1);
+                    queue.push_back(node);
+                }
+            }
+        }
+        dists
+    }
+
+    /// Finds the largest connected component in a simple graph by
+    /// checking SSSP repeatedly from different sources until all nodes
+    /// have been found.
+    ///
+    /// Returns a `HashSet` containing each node from that component.
+    ///
+    /// Should take O(V+E) time in total
+    fn largest_component(&self) -> HashSet<Self::Node> {
+        let graph = CsrGraph::from_graph(self);
+        largest_component_indices(&graph).into_iter().map(|i| graph.node(i)).collect()
+    }
+
+    /// Labels every node with its connected component.
+    ///
+    /// Components of equal size are ordered by their smallest node.
+    fn connected_components(&self) -> Components<Self::Node> {
+        let graph = CsrGraph::from_graph(self);
+        let mut component = vec![usize::MAX; graph.num_nodes()];
+        let mut found: Vec<usize> = vec![];
+        let mut ws = BfsWorkspace::new(graph.num_nodes());
+        for src in 0..graph.num_nodes() {
+            if component[src] != usize::MAX {
+                continue;
+            }
+            let reached = ws.run(&graph, src);
+            for &node in reached {
+                component[node] = found.len();
+            }
+            found.push(reached.len());
+        }
+        // renumber by decreasing size; the sort is stable so ties keep discovery order
+        let mut order: Vec<usize> = (0..found.len()).collect();
+        order.sort_by_key(|&c| std::cmp::Reverse(found[c]));
+        let mut rank = vec![0; found.len()];
+        for (new, &old) in order.iter().enumerate() {
+            rank[old] = new;
+        }
+        // CSR nodes are sorted, so each component's members come out sorted
+        let mut members_by_label: Vec<Vec<Self::Node>> = order.iter().map(|&c| Vec::with_capacity(found[c])).collect();
+        for (i, &c) in component.iter().enumerate() {
+            members_by_label[rank[c]].push(graph.node(i));
+        }
+        Components {
+            labels: component.iter().enumerate().map(|(i, &c)| (graph.node(i), rank[c])).collect(),
+            sizes: order.iter().map(|&c| found[c]).collect(),
+            members_by_label,
+        }
+    }
+
+    /// Computes max and mean shortest paths (aka diameter and mean
+    /// geodesic distance respectvely) of the largest connected component.
+    /// Other components are ignored; see `connected_components` and
+    /// `SimpleNetwork::induced_subgraph` to analyze them.
+    /// Uses SSSP from each vertex rather than APSP as it should be faster/easier
+    /// on simple graphs, despite the double counting. Additionally returns the
+    /// size of the largest component used.
+    ///
+    /// Should take O(V^2+VE) time. The searches run in parallel on the rayon
+    /// thread pool.
+    fn mgd_diameter(&self) -> (f64, u64, u64) {
+        let graph = CsrGraph::from_graph(self);
+        let component = largest_component_indices(&graph);
+        // summing over the whole adjacency matrix, but we get to throw out each row as we use it.
+        let (mgd, diameter) = component
+            .par_iter()
+            .map_init(
+                || BfsWorkspace::new(graph.num_nodes()),
+                |ws, &src| {
+                    ws.run(&graph, src);
+                    ws.reached().iter().fold((0.0, 0), |(sum_part, max_part), &node| {
+                        let dist = ws.distances()[node];
+                        (sum_part + (dist as f64), max(max_part, dist))
+                    })
+                },
+            )
+            .reduce(|| (0.0, 0), |(sum_1, max_1), (sum_2, max_2)| (sum_1 + sum_2, max(max_1, max_2)));
+        // no factor of two here because each path was counted twice - a little misleading name but works
+        let paths = component.len() * (component.len() - 1);
+        (mgd / (paths as f64), diameter as u64, component.len() as u64)
+    }
+
+    /// Exact diameter of the largest connected component, using iFUB from
+    /// its highest degree node instead of a search from every node
+    fn diameter(&self) -> Diameter {
+        let graph = CsrGraph::from_graph(self);
+        let component = largest_component_indices(&graph);
+        match component.iter().max_by_key(|&&node| graph.degree(node)) {
+            Some(&source) => ifub_diameter(&graph, source),
+            None => Diameter { diameter: 0, searches: 0 },
+        }
+    }
+
+    /// Computes the clustering coefficient (transitivity) for the graph
+    fn cluster_coeff(&self) -> f64 {
+        self.clustering().transitivity()
+    }
+
+    /// Mean degree of each node's neighbours, `k_nn`; 0 for isolated nodes
+    fn average_neighbor_degree(&self) -> HashMap<Self::Node, f64> {
+        let graph = CsrGraph::from_graph(self);
+        average_neighbor_degrees(&graph).into_iter().enumerate().map(|(i, knn)| (graph.node(i), knn)).collect()
+    }
+
+    /// `k_nn(k)`, the mean neighbour degree of the nodes of each degree
+    fn knn_spectrum(&self) -> Vec<KnnClass> {
+        knn_spectrum(&CsrGraph::from_graph(self))
+    }
+
+    fn joint_degree_matrix(&self) -> JointDegreeMatrix {
+        JointDegreeMatrix::from_csr(&CsrGraph::from_graph(self))
+    }
+
+    /// Newman's degree assortativity coefficient
+    fn degree_assortativity(&self) -> f64 {
+        self.joint_degree_matrix().assortativity()
+    }
+
+    /// Per-node triangle counts, for the local clustering coefficients and
+    /// the clustering spectrum
+    fn clustering(&self) -> Clustering<Self::Node> {
+        Clustering::from_csr(&CsrGraph::from_graph(self))
+    }
+
+    /// Harmonic centrality of every node, one (parallel) search per node
+    fn harmonic_centrality(&self) -> HashMap<Self::Node, f64> {
+        let graph = CsrGraph::from_graph(self);
+        let n = graph.num_nodes();
+        (0..n)
+            .into_par_iter()
+            .map_init(
+                || BfsWorkspace::new(n),
+                |ws, src| {
+                    ws.run(&graph, src);
+                    // the source itself comes first, at distance 0
+                    let total: f64 = ws.reached()[1..].iter().map(|&node| 1.0 / (ws.distances()[node] as f64)).sum();
+                    (graph.node(src), total / (n as f64 - 1.0))
+                },
+            )
+            .collect()
+    }
+
+    /// Distance sums from every node, for classic, per-component and
+    /// Wasserman-Faust closeness
+    fn closeness(&self) -> Closeness<Self::Node> {
+        Closeness::from_csr(&CsrGraph::from_graph(self))
+    }
+
+    /// The `k` nodes of highest Wasserman-Faust closeness, best first,
+    /// cutting short the searches that cannot make the top `k`
+    fn top_k_closeness(&self, k: usize) -> Vec<(Self::Node, f64)> {
+        let graph = CsrGraph::from_graph(self);
+        top_k_closeness(&graph, k).into_iter().map(|(i, score)| (graph.node(i), score)).collect()
+    }
+
+    /// The largest eigenvalue of the adjacency matrix
+    fn leading_eigenvalue(&self, convergence: Convergence) -> Result<f64> {
+        Ok(leading_eigenvector(&CsrGraph::from_graph(self), convergence)?.0)
+    }
+
+    /// Eigenvector centrality: the leading eigenvector of the adjacency
+    /// matrix, scaled to unit length
+    fn eigenvector_centrality(&self, convergence: Convergence) -> Result<HashMap<Self::Node, f64>> {
+        let graph = CsrGraph::from_graph(self);
+        let (_, x) = leading_eigenvector(&graph, convergence)?;
+        Ok(x.into_iter().enumerate().map(|(i, v)| (graph.node(i), v)).collect())
+    }
+
+    /// Katz centrality `x = alpha A x + 1`. Fails unless
+    /// `0 < alpha < 1 / lambda_max`
+    fn katz_centrality(&self, alpha: f64, convergence: Convergence) -> Result<HashMap<Self::Node, f64>> {
+        let graph = CsrGraph::from_graph(self);
+        let x = katz(&graph, alpha, convergence)?;
+        Ok(x.into_iter().enumerate().map(|(i, v)| (graph.node(i), v)).collect())
+    }
+
+    /// PageRank with the given damping factor, restarting at nodes drawn
+    /// from `personalization` (uniformly if `None`; nodes missing from it get
+    /// weight 0). Isolated nodes always restart
+    fn pagerank(
+        &self,
+        damping: f64,
+        personalization: Option<&HashMap<Self::Node, f64>>,
+        convergence: Convergence,
+    ) -> Result<HashMap<Self::Node, f64>> {
+        let graph = CsrGraph::from_graph(self);
+        let weights: Option<Vec<f64>> = personalization
+            .map(|weights| graph.nodes().iter().map(|node| weights.get(node).copied().unwrap_or(0.0)).collect());
+        let x = pagerank(&graph, damping, weights.as_deref(), convergence)?;
+        Ok(x.into_iter().enumerate().map(|(i, v)| (graph.node(i), v)).collect())
+    }
+
+    /// SSSP that also returns paths
+    fn sssp_verbose(&self, from: Self::Node) -> HashMap<Self::Node, Vec<Self::Node>> {
+        // Map to store in-progress paths, from all nodes
+        let mut paths: HashMap<Self::Node, Vec<Self::Node>> = self.nodes().map(|node| (node, vec![])).collect();
+        // Map to return, and FIFO queue for next vertices to check
+        let mut dists = HashMap::new();
+        let mut queue = VecDeque::new();
+        dists.insert(from, vec![]);
+        queue.push_back(from);
+        while let Some(next) = queue.pop_front() {
+            // check for any neighbors we haven't seen
+            for node in self.neighbors(next) {
+                if let Entry::Vacant(entry) = dists.entry(node) {
+                    if let Some(pvec) = paths.get_mut(&node) {
+                        pvec.push(next);
+                        entry.insert(pvec.clone());
+                    }
+                    queue.push_back(node);
+                }
+            }
+        }
+        dists
+    }
+
+    /// Counts how often each node appears on the paths from `sssp_verbose`,
+    /// with the sources searched in parallel
+    fn betweenness_centrality(&self) -> HashMap<Self::Node, u64>
+    where
+        Self: Sync,
+    {
+        let nodes: Vec<Self::Node> = self.nodes().collect();
+        let empty = || nodes.iter().map(|node| (*node, 0)).collect::<HashMap<Self::Node, u64>>();
+        // check per node's sssp, each thread counting into its own map
+        nodes
+            .par_iter()
+            .fold(empty, |mut result, node| {
+                for path in self.sssp_verbose(*node).into_values() {
+                    for i in &path {
+                        if let Some(count) = result.get_mut(i) {
+                            *count += 1;
+                        }
+                    }
+                }
+                result
+            })
+            .reduce(empty, |mut total, part| {
+                for (node, count) in part {
+                    *total.entry(node).or_insert(0) += count;
+                }
+                total
+            })
+    }
+
+    /// Every ordered pair of distinct nodes that are not adjacent
+    fn get_nonedges(&self) -> HashSet<(Self::Node, Self::Node)> {
+        let mut pairs = HashSet::new();
+        for src in self.nodes() {
+            for dest in self.nodes() {
+                if src != dest && !self.has_edge(src, dest) {
+                    pairs.insert((src, dest));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Every edge in both directions
+    fn edgelist(&self) -> HashSet<(Self::Node, Self::Node)> {
+        self.nodes()
+            .flat_map(|src| self.neighbors(src).map(move |dst| (src, dst)))
+            .collect()
+    }
+
+    /// Distances between every pair of nodes, `-1` if unreachable.
+    ///
+    /// Uses node ids as indices, so assumes they are `0..n-1`; will take a
+    /// lot of space.
+    fn apsp(&self) -> Vec<Vec<i64>> {
+        let graph = CsrGraph::from_graph(self);
+        let n = graph.num_nodes();
+        let mut all_paths = vec![vec![-1; n]; n];
+        // rows are computed in parallel, one search per source
+        let rows: Vec<(usize, Vec<i64>)> = (0..n)
+            .into_par_iter()
+            .map_init(
+                || BfsWorkspace::new(n),
+                |ws, src| {
+                    let mut row = vec![-1; n];
+                    ws.run(&graph, src);
+                    for &dst in ws.reached() {
+                        row[graph.node(dst).id() as usize] = ws.distances()[dst] as i64;
+                    }
+                    (graph.node(src).id() as usize, row)
+                },
+            )
+            .collect();
+        for (id, row) in rows {
+            all_paths[id] = row;
+        }
+        all_paths
+    }
+}
+
+impl<G: Graph + ?Sized> Analysis for G {}
+
+/// Node indices of the largest connected component of `graph`, found with
+/// one search per component
+pub(crate) fn largest_component_indices<N: Node>(graph: &CsrGraph<N>) -> Vec<usize> {
+    let mut reached = vec![false; graph.num_nodes()];
+    let mut ws = BfsWorkspace::new(graph.num_nodes());
+    let mut largest = vec![];
+    for src in 0..graph.num_nodes() {
+        if reached[src] {
+            continue;
+        }
+        let component = ws.run(graph, src);
+        for &node in component {
+            reached[node] = true;
+        }
+        if component.len() > largest.len() {
+            largest = component.to_vec();
+        }
+    }
+    largest
+}
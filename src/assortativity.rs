@@ -0,0 +1,171 @@
+//! Degree correlations: mean neighbour degree and degree assortativity.
+//!
+//! `k_nn(i)` is the mean degree of node `i`'s neighbours, and `k_nn(k)` its
+//! mean over the nodes of degree `k`; a rising `k_nn(k)` means high degree
+//! nodes attach to each other. [`JointDegreeMatrix`] counts the edges
+//! between each pair of degrees, and Newman's assortativity coefficient `r`
+//! (Newman 2002) is the Pearson correlation of the degrees at either end of
+//! an edge, computed from it.
+
+use std::collections::BTreeMap;
+
+use crate::csr::CsrGraph;
+use crate::node::Node;
+use crate::output::{Field, Row};
+
+/// Mean neighbour degree of each node index of `graph`; 0 for isolated
+/// nodes.
+pub fn average_neighbor_degrees<N: Node>(graph: &CsrGraph<N>) -> Vec<f64> {
+    (0..graph.num_nodes())
+        .map(|node| {
+            let nbrs = graph.neighbors(node);
+            if nbrs.is_empty() {
+                0.0
+            } else {
+                nbrs.iter().map(|&nbr| graph.degree(nbr)).sum::<usize>() as f64 / nbrs.len() as f64
+            }
+        })
+        .collect()
+}
+
+/// One degree class of the `k_nn(k)` spectrum.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KnnClass {
+    pub degree: usize,
+    /// Number of nodes with this degree
+    pub count: usize,
+    /// Mean neighbour degree of those nodes, `k_nn(k)`
+    pub knn: f64,
+}
+
+/// `k_nn(k)` for each degree `k >= 1` present in `graph`, in increasing
+/// order of degree.
+pub fn knn_spectrum<N: Node>(graph: &CsrGraph<N>) -> Vec<KnnClass> {
+    let mut classes: BTreeMap<usize, (usize, f64)> = BTreeMap::new();
+    for (node, knn) in average_neighbor_degrees(graph).into_iter().enumerate() {
+        let degree = graph.degree(node);
+        if degree > 0 {
+            let class = classes.entry(degree).or_default();
+            class.0 += 1;
+            class.1 += knn;
+        }
+    }
+    classes
+        .into_iter()
+        .map(|(degree, (count, total))| KnnClass { degree, count, knn: total / count as f64 })
+        .collect()
+}
+
+/// Number of edge ends joining a node of one degree to a node of another.
+///
+/// Each edge is counted from both ends, so the matrix is symmetric and its
+/// entries sum to twice the number of edges; `probability(j, k)` is then the
+/// `e_jk` of Newman's paper.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct JointDegreeMatrix {
+    counts: BTreeMap<(usize, usize), u64>,
+    total: u64,
+}
+
+/// One nonzero entry of a [`JointDegreeMatrix`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointDegreeEntry {
+    pub degree: usize,
+    pub neighbor_degree: usize,
+    pub count: u64,
+    pub probability: f64,
+}
+
+impl JointDegreeMatrix {
+    pub fn from_csr<N: Node>(graph: &CsrGraph<N>) -> Self {
+        let mut matrix = Self::default();
+        for node in 0..graph.num_nodes() {
+            for &nbr in graph.neighbors(node) {
+                *matrix.counts.entry((graph.degree(node), graph.degree(nbr))).or_insert(0) += 1;
+                matrix.total += 1;
+            }
+        }
+        matrix
+    }
+
+    pub fn count(&self, degree: usize, neighbor_degree: usize) -> u64 {
+        self.counts.get(&(degree, neighbor_degree)).copied().unwrap_or(0)
+    }
+
+    /// Fraction of edge ends joining degree `degree` to `neighbor_degree`
+    pub fn probability(&self, degree: usize, neighbor_degree: usize) -> f64 {
+        self.count(degree, neighbor_degree) as f64 / self.total as f64
+    }
+
+    /// The nonzero entries, ordered by degree then neighbour degree
+    pub fn entries(&self) -> impl Iterator<Item = JointDegreeEntry> + '_ {
+        self.counts.iter().map(|(&(degree, neighbor_degree), &count)| JointDegreeEntry {
+            degree,
+            neighbor_degree,
+            count,
+            probability: count as f64 / self.total as f64,
+        })
+    }
+
+    /// Newman's degree assortativity coefficient `r`, in `[-1, 1]`. NaN if
+    /// there are no edges or every edge joins nodes of the same degree pair
+    /// (e.g. a regular graph).
+    pub fn assortativity(&self) -> f64 {
+        let (mut mean, mut square, mut product) = (0.0, 0.0, 0.0);
+        for entry in self.entries() {
+            let (j, k) = (entry.degree as f64, entry.neighbor_degree as f64);
+            mean += j * entry.probability;
+            square += j * j * entry.probability;
+            product += j * k * entry.probability;
+        }
+        (product - mean * mean) / (square - mean * mean)
+    }
+}
+
+impl Row for KnnClass {
+    const COLUMNS: &'static [&'static str] = &["degree", "nodes", "knn"];
+
+    fn fields(&self) -> Vec<Field> {
+        vec![self.degree.into(), self.count.into(), self.knn.into()]
+    }
+}
+
+impl Row for JointDegreeEntry {
+    const COLUMNS: &'static [&'static str] = &["degree", "neighbor_degree", "count", "probability"];
+
+    fn fields(&self) -> Vec<Field> {
+        vec![
+            self.degree.into(),
+            self.neighbor_degree.into(),
+            self.count.into(),
+            self.probability.into(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::Analysis;
+    use crate::SimpleNetwork;
+
+    #[test]
+    fn stars_are_disassortative() -> crate::Result<()> {
+        // a star with centre 0 and three leaves, plus a disjoint edge 4-5
+        let network = SimpleNetwork::from_edges((0..6u64).collect(), [(0, 1), (0, 2), (0, 3), (4, 5)])?;
+        let knn = network.average_neighbor_degree();
+        assert_eq!((knn[&0], knn[&1], knn[&4]), (1.0, 3.0, 1.0));
+        let spectrum = network.knn_spectrum();
+        assert_eq!(spectrum.iter().map(|c| (c.degree, c.count, c.knn)).collect::<Vec<_>>(), vec![(1, 5, 2.2), (3, 1, 1.0)]);
+
+        let joint = network.joint_degree_matrix();
+        assert_eq!((joint.count(1, 3), joint.count(3, 1), joint.count(1, 1)), (3, 3, 2));
+        assert_eq!(joint.entries().map(|e| e.probability).sum::<f64>(), 1.0);
+        // over the 8 edge ends, E[j] = 14/8, E[j^2] = 32/8 and E[jk] = 20/8
+        assert!((network.degree_assortativity() + 0.6).abs() < 1e-12);
+
+        // the star alone is perfectly disassortative
+        let star = network.induced_subgraph([0, 1, 2, 3]);
+        assert!((star.degree_assortativity() + 1.0).abs() < 1e-12);
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+//! Unweighted breadth-first search over a [`CsrGraph`].
+//!
+//! Every edge has length one, so a plain FIFO queue visits nodes in order of
+//! distance and no priority queue is needed. Distances live in a dense array
+//! indexed by node index. A [`BfsWorkspace`] keeps that array and the queue
+//! between searches, so running one search per source allocates only once.
+
+use crate::csr::CsrGraph;
+use crate::node::Node;
+
+/// Distance of a node the last search did not reach.
+pub const UNREACHED: u32 = u32::MAX;
+
+/// Reusable scratch space for breadth-first searches.
+#[derive(Debug, Clone, Default)]
+pub struct BfsWorkspace {
+    dist: Vec<u32>,
+    /// Nodes reached by the last search, in the order they were visited;
+    /// doubles as the FIFO queue while searching.
+    queue: Vec<usize>,
+}
+
+impl BfsWorkspace {
+    /// A workspace for graphs of up to `n` nodes (it grows if needed).
+    pub fn new(n: usize) -> Self {
+        Self {
+            dist: vec![UNREACHED; n],
+            queue: Vec::with_capacity(n),
+        }
+    }
+
+    /// Searches from node index `source`, returning the reached nodes in
+    /// order of distance (`source` first).
+    pub fn run<N: Node>(&mut self, graph: &CsrGraph<N>, source: usize) -> &[usize] {
+        self.run_while(graph, source, |_, _| true);
+        &self.queue
+    }
+
+    /// Searches from node index `source` level by level. Each time every
+    /// node within `depth` of `source` has been reached, and no node further
+    /// out, calls `proceed(depth, reached)` and abandons the search if it
+    /// returns false. Returns whether the search ran to the end.
+    pub fn run_while<N, F>(&mut self, graph: &CsrGraph<N>, source: usize, mut proceed: F) -> bool
+    where
+        N: Node,
+        F: FnMut(u32, &[usize]) -> bool,
+    {
+        // only the entries touched by the last search need resetting
+        for &v in &self.queue {
+            self.dist[v] = UNREACHED;
+        }
+        self.queue.clear();
+        if self.dist.len() < graph.num_nodes() {
+            self.dist.resize(graph.num_nodes(), UNREACHED);
+        }
+
+        self.dist[source] = 0;
+        self.queue.push(source);
+        let mut depth = 0;
+        if !proceed(depth, &self.queue) {
+            return false;
+        }
+        let mut head = 0;
+        while head < self.queue.len() {
+            let u = self.queue[head];
+            head += 1;
+            // the first node of a new level: the whole level is queued
+            if self.dist[u] > depth {
+                depth = self.dist[u];
+                if !proceed(depth, &self.queue) {
+                    return false;
+                }
+            }
+            let d = self.dist[u] + 1;
+            for &v in graph.neighbors(u) {
+                if self.dist[v] == UNREACHED {
+                    self.dist[v] = d;
+                    self.queue.push(v);
+                }
+            }
+        }
+        true
+    }
+
+    /// Distance to node index `i` in the last search, if it was reached.
+    pub fn distance(&self, i: usize) -> Option<u32> {
+        self.dist.get(i).copied().filter(|&d| d != UNREACHED)
+    }
+
+    /// Distances from the last search, [`UNREACHED`] for nodes it missed.
+    pub fn distances(&self) -> &[u32] {
+        &self.dist
+    }
+
+    /// Nodes reached by the last search, in order of distance.
+    pub fn reached(&self) -> &[usize] {
+        &self.queue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimpleNetwork;
+
+    #[test]
+    fn workspace_is_reset_between_searches() -> crate::Result<()> {
+        // path 1-2-3-4 plus a separate edge 5-6
+        let network = SimpleNetwork::from_edges(vec![1u64, 2, 3, 4, 5, 6], [(1, 2), (2, 3), (3, 4), (5, 6)])?;
+        let graph = CsrGraph::from_network(&network);
+        let mut ws = BfsWorkspace::new(0);
+
+        assert_eq!(ws.run(&graph, 0), &[0, 1, 2, 3]);
+        assert_eq!(ws.distance(3), Some(3));
+        assert_eq!(ws.distance(4), None);
+
+        assert_eq!(ws.run(&graph, 5), &[5, 4]);
+        assert_eq!(ws.distance(4), Some(1));
+        assert_eq!(ws.distance(0), None);
+        assert_eq!(ws.distances().iter().filter(|&&d| d != UNREACHED).count(), 2);
+        Ok(())
+    }
+}
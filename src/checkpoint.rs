@@ -0,0 +1,266 @@
+//! Checkpoints, so that long ensembles can be resumed after an interruption.
+//!
+//! A run is split into named units of work (a dataset, a replicate, a sweep
+//! step). After each unit the checkpoint records that it is done, the length
+//! of every output file, the RNG state and, for MCMC runs, a snapshot of the
+//! chain. Resuming skips the finished units and cuts the output files back to
+//! their recorded lengths, so rows from a unit that was interrupted half-way
+//! are written once, not twice.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::snapshot::Snapshot;
+use crate::SimpleNetwork;
+
+/// The position of a [`ChaCha8Rng`] stream.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RngState {
+    pub seed: [u8; 32],
+    pub word_pos: u128,
+}
+
+impl RngState {
+    pub fn capture(rng: &ChaCha8Rng) -> Self {
+        Self {
+            seed: rng.get_seed(),
+            word_pos: rng.get_word_pos(),
+        }
+    }
+
+    /// An RNG that continues exactly where the captured one was.
+    pub fn restore(&self) -> ChaCha8Rng {
+        let mut rng = ChaCha8Rng::from_seed(self.seed);
+        rng.set_word_pos(self.word_pos);
+        rng
+    }
+}
+
+/// Where a Markov chain (e.g. a run of double edge swaps) had got to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainState {
+    /// The dataset the chain was started from
+    pub dataset: String,
+    /// How far the chain had got, in whatever steps the caller counts
+    pub step: u64,
+    /// Snapshot of the network at `step`
+    pub snapshot: PathBuf,
+}
+
+/// Progress of one invocation, saved as JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Identifies the invocation; a checkpoint is only resumed by the same one
+    pub key: String,
+    /// Run id of the original invocation, kept so resumed rows share it
+    pub run_id: String,
+    /// Seed of the original invocation, kept so the resumed run records it
+    #[serde(default)]
+    pub seed: Option<u64>,
+    pub rng: RngState,
+    pub completed: BTreeSet<String>,
+    /// Length in bytes of each output file as of the last completed unit
+    pub outputs: BTreeMap<PathBuf, u64>,
+    pub chain: Option<ChainState>,
+    #[serde(skip)]
+    path: PathBuf,
+    /// Outputs already opened (and so already cut back) by this process
+    #[serde(skip)]
+    opened: HashSet<PathBuf>,
+}
+
+impl Checkpoint {
+    pub fn new<P: AsRef<Path>>(path: P, key: &str, run_id: &str, rng: &ChaCha8Rng) -> Self {
+        Self {
+            key: key.to_owned(),
+            run_id: run_id.to_owned(),
+            seed: None,
+            rng: RngState::capture(rng),
+            completed: BTreeSet::new(),
+            outputs: BTreeMap::new(),
+            chain: None,
+            path: path.as_ref().to_path_buf(),
+            opened: HashSet::new(),
+        }
+    }
+
+    /// Loads the checkpoint at `path`, or `None` if there isn't one.
+    ///
+    /// A checkpoint written by a different invocation is refused rather than
+    /// resumed with the wrong settings.
+    pub fn resume<P: AsRef<Path>>(path: P, key: &str) -> Result<Option<Self>> {
+        let path = path.as_ref();
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let mut checkpoint: Self = serde_json::from_reader(file)?;
+        if checkpoint.key != key {
+            return Err(Error::InvalidParameter(format!(
+                "checkpoint {} was written by a different command; remove it to start over",
+                path.display()
+            )));
+        }
+        checkpoint.path = path.to_path_buf();
+        Ok(Some(checkpoint))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_done(&self, unit: &str) -> bool {
+        self.completed.contains(unit)
+    }
+
+    /// Registers an output file before it is written to.
+    ///
+    /// The first time a file is seen it is cut back to its recorded length,
+    /// or, if it has none, its current length is recorded.
+    pub fn track_output<P: AsRef<Path>>(&mut self, file: P) -> Result<()> {
+        let file = file.as_ref().to_path_buf();
+        if !self.opened.insert(file.clone()) {
+            return Ok(());
+        }
+        match self.outputs.get(&file) {
+            Some(&len) => {
+                if let Ok(f) = File::options().write(true).open(&file) {
+                    if f.metadata()?.len() > len {
+                        f.set_len(len)?;
+                    }
+                }
+            }
+            None => {
+                let len = fs::metadata(&file).map(|m| m.len()).unwrap_or(0);
+                self.outputs.insert(file, len);
+                self.save()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Marks `unit` as done and saves the checkpoint.
+    pub fn complete(&mut self, unit: &str, rng: &ChaCha8Rng) -> Result<()> {
+        for (file, len) in self.outputs.iter_mut() {
+            *len = fs::metadata(file).map(|m| m.len()).unwrap_or(0);
+        }
+        self.completed.insert(unit.to_owned());
+        self.rng = RngState::capture(rng);
+        self.save()
+    }
+
+    /// Snapshots a chain's network; it becomes part of the checkpoint at the
+    /// next [`complete`](Self::complete).
+    pub fn save_chain(&mut self, dataset: &str, step: u64, network: &SimpleNetwork<u64>) -> Result<()> {
+        let file_safe: String = dataset
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+            .collect();
+        let snapshot = PathBuf::from(format!("{}.{}-{}.snap", self.path.display(), file_safe, step));
+        Snapshot::from_network(network).save(&snapshot)?;
+        self.chain = Some(ChainState {
+            dataset: dataset.to_owned(),
+            step,
+            snapshot,
+        });
+        Ok(())
+    }
+
+    /// The saved chain for `dataset`, with the number of steps it had taken.
+    pub fn load_chain(&self, dataset: &str) -> Result<Option<(u64, SimpleNetwork<u64>)>> {
+        match &self.chain {
+            Some(chain) if chain.dataset == dataset => {
+                Ok(Some((chain.step, Snapshot::load(&chain.snapshot)?.to_network()?)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Writes the checkpoint atomically, then drops chain snapshots it no
+    /// longer refers to.
+    pub fn save(&self) -> Result<()> {
+        let tmp = PathBuf::from(format!("{}.tmp", self.path.display()));
+        serde_json::to_writer(File::create(&tmp)?, self)?;
+        fs::rename(&tmp, &self.path)?;
+        self.remove_old_chains(self.chain.as_ref().map(|c| c.snapshot.as_path()))
+    }
+
+    /// Removes the checkpoint once the run has finished.
+    pub fn finish(self) -> Result<()> {
+        self.remove_old_chains(None)?;
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn remove_old_chains(&self, keep: Option<&Path>) -> Result<()> {
+        let (Some(dir), Some(name)) = (self.path.parent(), self.path.file_name().and_then(|n| n.to_str())) else {
+            return Ok(());
+        };
+        let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+        let prefix = format!("{}.", name);
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let is_chain = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(&prefix) && n.ends_with(".snap"));
+            if is_chain && keep.and_then(Path::file_name) != path.file_name() {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use std::io::Write;
+
+    #[test]
+    fn resume_cuts_outputs_and_restores_rng() -> crate::Result<()> {
+        let dir = std::env::temp_dir().join(format!("hw5352-checkpoint-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("run.checkpoint");
+        let out = dir.join("out.csv");
+        fs::write(&out, "old\n")?;
+
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let mut checkpoint = Checkpoint::new(&path, "stats x", "run", &rng);
+        checkpoint.track_output(&out)?;
+        File::options().append(true).open(&out)?.write_all(b"unit 0\n")?;
+        rng.gen::<u64>();
+        let network = SimpleNetwork::from_edges(vec![1u64, 2, 3], [(1, 2)])?;
+        checkpoint.save_chain("0/x", 5, &network)?;
+        checkpoint.complete("x/0", &rng)?;
+        let expected: u64 = rng.gen();
+
+        // interrupted half-way through the next unit
+        File::options().append(true).open(&out)?.write_all(b"partial\n")?;
+
+        assert!(Checkpoint::resume(&path, "stats y").is_err());
+        let mut resumed = Checkpoint::resume(&path, "stats x")?.unwrap();
+        assert!(resumed.is_done("x/0") && !resumed.is_done("x/1"));
+        assert_eq!(resumed.rng.restore().gen::<u64>(), expected);
+        resumed.track_output(&out)?;
+        assert_eq!(fs::read_to_string(&out)?, "old\nunit 0\n");
+        let (step, chain) = resumed.load_chain("0/x")?.unwrap();
+        assert_eq!((step, chain.num_edges()), (5, 1));
+
+        resumed.finish()?;
+        assert!(!path.exists());
+        assert_eq!(fs::read_dir(&dir)?.count(), 1);
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
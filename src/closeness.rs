@@ -0,0 +1,184 @@
+//! Closeness centrality, with the normalizations that keep it meaningful on
+//! disconnected networks.
+//!
+//! Classic closeness is the inverse of the mean distance to the other
+//! nodes, which is infinite as soon as one is unreachable. Taking the mean
+//! over the `r` nodes a node can reach instead (per-component
+//! normalization) makes the nodes of a small component look central, so
+//! Wasserman and Faust (1994) scale it by the fraction of the network
+//! reached: `C_WF(v) = (r / (n - 1)) * (r / farness(v))`. On a connected
+//! network the two agree with the classic definition.
+//!
+//! [`top_k_closeness`] finds the `k` nodes of highest `C_WF` without
+//! finishing most searches, after Bergamini et al. (2016): it searches
+//! from nodes in decreasing order of degree, and abandons a search once a
+//! lower bound on the farness (every node not yet reached is at least one
+//! level further out) shows it cannot beat the current `k`-th best.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use rayon::prelude::*;
+
+use crate::bfs::BfsWorkspace;
+use crate::csr::CsrGraph;
+use crate::node::Node;
+
+/// Distance sums from every node, from which the closeness variants follow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Closeness<N: Node = u64> {
+    /// Every node, sorted; the other fields line up with it
+    pub nodes: Vec<N>,
+    /// Sum of the distances to the nodes each can reach
+    pub farness: Vec<u64>,
+    /// Number of other nodes each can reach: its component's size less one
+    pub reachable: Vec<usize>,
+    /// Sum of the inverse distances to the nodes each can reach
+    pub harmonic: Vec<f64>,
+}
+
+impl<N: Node> Closeness<N> {
+    pub fn from_csr(graph: &CsrGraph<N>) -> Self {
+        let n = graph.num_nodes();
+        let sums: Vec<(u64, usize, f64)> = (0..n)
+            .into_par_iter()
+            .map_init(
+                || BfsWorkspace::new(n),
+                |ws, src| {
+                    ws.run(graph, src);
+                    let dists = ws.reached()[1..].iter().map(|&node| ws.distances()[node]);
+                    let (farness, harmonic) = dists.fold((0, 0.0), |(f, h), d| (f + d as u64, h + 1.0 / d as f64));
+                    (farness, ws.reached().len() - 1, harmonic)
+                },
+            )
+            .collect();
+        Self {
+            nodes: graph.nodes().to_vec(),
+            farness: sums.iter().map(|s| s.0).collect(),
+            reachable: sums.iter().map(|s| s.1).collect(),
+            harmonic: sums.iter().map(|s| s.2).collect(),
+        }
+    }
+
+    /// Inverse mean distance to the nodes each can reach (0 for isolated
+    /// nodes)
+    pub fn per_component(&self) -> Vec<f64> {
+        self.farness.iter().zip(&self.reachable).map(|(&f, &r)| if r == 0 { 0.0 } else { r as f64 / f as f64 }).collect()
+    }
+
+    /// Closeness with the Wasserman-Faust correction
+    pub fn wasserman_faust(&self) -> Vec<f64> {
+        let others = self.nodes.len().saturating_sub(1) as f64;
+        self.farness.iter().zip(&self.reachable).map(|(&f, &r)| wasserman_faust(f, r, others)).collect()
+    }
+
+    /// Harmonic centrality normalized within each component: the mean
+    /// inverse distance to the nodes each can reach
+    pub fn harmonic_per_component(&self) -> Vec<f64> {
+        self.harmonic.iter().zip(&self.reachable).map(|(&h, &r)| if r == 0 { 0.0 } else { h / r as f64 }).collect()
+    }
+}
+
+fn wasserman_faust(farness: u64, reachable: usize, others: f64) -> f64 {
+    if reachable == 0 {
+        0.0
+    } else {
+        let r = reachable as f64;
+        (r / others) * (r / farness as f64)
+    }
+}
+
+/// The `k` node indices of highest Wasserman-Faust closeness, best first,
+/// with their closeness. Ties go to the node searched first: the one of
+/// higher degree, then of lower index.
+pub fn top_k_closeness<N: Node>(graph: &CsrGraph<N>, k: usize) -> Vec<(usize, f64)> {
+    let n = graph.num_nodes();
+    let others = n.saturating_sub(1) as f64;
+    let reachable = component_sizes(graph).into_iter().map(|size| size - 1).collect::<Vec<_>>();
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by_key(|&node| (Reverse(graph.degree(node)), node));
+
+    // the best k so far by search position, worst (and, among ties, latest
+    // searched) on top; scores are nonnegative, so their bit patterns order
+    // like the floats
+    let mut best: BinaryHeap<Reverse<(u64, Reverse<usize>)>> = BinaryHeap::new();
+    let mut ws = BfsWorkspace::new(n);
+    for (position, &source) in order.iter().enumerate() {
+        let r = reachable[source];
+        let threshold = if best.len() < k { -1.0 } else { best.peek().map_or(-1.0, |worst| f64::from_bits(worst.0 .0)) };
+        let (mut farness, mut counted) = (0u64, 1);
+        let finished = ws.run_while(graph, source, |depth, reached| {
+            farness += depth as u64 * (reached.len() - counted) as u64;
+            counted = reached.len();
+            let bound = farness + (depth as u64 + 1) * (r + 1 - counted) as u64;
+            wasserman_faust(bound, r, others) > threshold
+        });
+        if !finished {
+            continue;
+        }
+        let farness = ws.reached().iter().map(|&node| ws.distances()[node] as u64).sum();
+        let score = wasserman_faust(farness, r, others);
+        if score > threshold {
+            best.push(Reverse((score.to_bits(), Reverse(position))));
+            if best.len() > k {
+                best.pop();
+            }
+        }
+    }
+    let mut top: Vec<(usize, f64)> =
+        best.into_iter().map(|Reverse((score, Reverse(position)))| (order[position], f64::from_bits(score))).collect();
+    top.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    top
+}
+
+/// Size of the component of each node index
+fn component_sizes<N: Node>(graph: &CsrGraph<N>) -> Vec<usize> {
+    let mut sizes = vec![0; graph.num_nodes()];
+    let mut ws = BfsWorkspace::new(graph.num_nodes());
+    for node in 0..graph.num_nodes() {
+        if sizes[node] == 0 {
+            let component = ws.run(graph, node);
+            for &member in component {
+                sizes[member] = component.len();
+            }
+        }
+    }
+    sizes
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::Analysis;
+    use crate::network::random_network;
+    use crate::SimpleNetwork;
+
+    #[test]
+    fn top_k_matches_full_ranking() -> crate::Result<()> {
+        // path 0-1-2 and an edge 3-4
+        let network = SimpleNetwork::from_edges((0..5u64).collect(), [(0, 1), (1, 2), (3, 4)])?;
+        let closeness = network.closeness();
+        assert_eq!(closeness.per_component(), vec![2.0 / 3.0, 1.0, 2.0 / 3.0, 1.0, 1.0]);
+        // the centre of the path reaches half the network, the pair a quarter
+        assert_eq!(closeness.wasserman_faust(), vec![1.0 / 3.0, 0.5, 1.0 / 3.0, 0.25, 0.25]);
+        assert_eq!(closeness.harmonic_per_component()[0], 0.75);
+
+        // a sparse random graph with several components
+        let network = random_network(200, 0.011, 3);
+        let closeness = network.closeness();
+        let mut scores = closeness.wasserman_faust();
+        scores.sort_by(|a, b| b.total_cmp(a));
+        let top = network.top_k_closeness(10);
+        assert_eq!(top.len(), 10);
+        for (i, (node, score)) in top.iter().enumerate() {
+            assert_eq!(*score, scores[i]);
+            assert_eq!(closeness.wasserman_faust()[*node as usize], *score);
+        }
+
+        // 3 and 6 tie behind 4; 6 has the higher degree, so is searched
+        // first and keeps its place when 4 pushes one of them out
+        let network = SimpleNetwork::from_edges((0..7u64).collect(), [(0, 3), (0, 5), (1, 6), (2, 6), (3, 4), (4, 6)])?;
+        assert_eq!(network.closeness().wasserman_faust()[3], network.closeness().wasserman_faust()[6]);
+        assert_eq!(network.top_k_closeness(2), vec![(4, 6.0 / 11.0), (6, 0.5)]);
+        Ok(())
+    }
+}
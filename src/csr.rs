@@ -0,0 +1,149 @@
+//! Compressed sparse row form of a network.
+//!
+//! Nodes are numbered `0..n` in sorted order and each node's neighbors are
+//! stored as a contiguous, sorted run of indices in one shared array. This is
+//! much cheaper to traverse than the `HashMap` of `HashSet`s in
+//! [`SimpleNetwork`], and is what gets written to snapshots.
+
+use crate::error::{Error, Result};
+use crate::graph::Graph;
+use crate::node::Node;
+use crate::SimpleNetwork;
+
+/// An immutable, undirected network in compressed sparse row form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsrGraph<N: Node = u64> {
+    nodes: Vec<N>,
+    offsets: Vec<usize>,
+    targets: Vec<usize>,
+}
+
+impl<N: Node> CsrGraph<N> {
+    /// Builds the CSR form from raw parts.
+    ///
+    /// `nodes` must be sorted, `offsets` must have `nodes.len() + 1` entries
+    /// and every target must be a valid node index.
+    pub fn from_parts(nodes: Vec<N>, offsets: Vec<usize>, targets: Vec<usize>) -> Result<Self> {
+        if offsets.len() != nodes.len() + 1 || offsets.first() != Some(&0) || offsets.last() != Some(&targets.len()) {
+            return Err(Error::InvalidData("offsets do not match node and target counts".into()));
+        }
+        if !nodes.windows(2).all(|w| w[0] < w[1]) {
+            return Err(Error::InvalidData("nodes are not sorted and unique".into()));
+        }
+        if !offsets.windows(2).all(|w| w[0] <= w[1]) || targets.iter().any(|&t| t >= nodes.len()) {
+            return Err(Error::InvalidData("malformed adjacency".into()));
+        }
+        Ok(Self { nodes, offsets, targets })
+    }
+
+    pub fn from_network(network: &SimpleNetwork<N>) -> Self {
+        Self::from_graph(network)
+    }
+
+    /// Builds the CSR form of any [`Graph`], e.g. a view.
+    pub fn from_graph<G: Graph<Node = N> + ?Sized>(graph: &G) -> Self {
+        let mut nodes: Vec<N> = graph.nodes().collect();
+        nodes.sort();
+
+        let mut offsets = Vec::with_capacity(nodes.len() + 1);
+        let mut targets = Vec::new();
+        offsets.push(0);
+        for node in &nodes {
+            let start = targets.len();
+            targets.extend(graph.neighbors(*node).filter_map(|nbr| nodes.binary_search(&nbr).ok()));
+            targets[start..].sort_unstable();
+            offsets.push(targets.len());
+        }
+        Self { nodes, offsets, targets }
+    }
+
+    /// Builds the adjacency-list form. Each edge is added from its lower
+    /// index end; a target listed twice is an [`Error::DuplicateEdge`].
+    pub fn to_network(&self) -> Result<SimpleNetwork<N>> {
+        let mut network = SimpleNetwork::from_node_vec(self.nodes.clone());
+        for (i, node) in self.nodes.iter().enumerate() {
+            for &j in self.neighbors(i).iter().filter(|&&j| j > i) {
+                network.add_edge(*node, self.nodes[j])?;
+            }
+        }
+        Ok(network)
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Number of undirected edges (half the stored adjacency entries).
+    pub fn num_edges(&self) -> usize {
+        self.targets.len() / 2
+    }
+
+    /// The node at index `i`.
+    pub fn node(&self, i: usize) -> N {
+        self.nodes[i]
+    }
+
+    /// The index of `node`, if it is in the graph.
+    pub fn index_of(&self, node: &N) -> Option<usize> {
+        self.nodes.binary_search(node).ok()
+    }
+
+    /// Indices of the neighbors of node `i`, sorted.
+    pub fn neighbors(&self, i: usize) -> &[usize] {
+        &self.targets[self.offsets[i]..self.offsets[i + 1]]
+    }
+
+    pub fn degree(&self, i: usize) -> usize {
+        self.offsets[i + 1] - self.offsets[i]
+    }
+
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    pub fn offsets(&self) -> &[usize] {
+        &self.offsets
+    }
+
+    pub fn targets(&self) -> &[usize] {
+        &self.targets
+    }
+}
+
+impl<N: Node> Graph for CsrGraph<N> {
+    type Node = N;
+
+    fn nodes(&self) -> impl Iterator<Item = N> + '_ {
+        self.nodes.iter().copied()
+    }
+
+    fn neighbors(&self, node: N) -> impl Iterator<Item = N> + '_ {
+        self.index_of(&node)
+            .into_iter()
+            .flat_map(move |i| CsrGraph::neighbors(self, i).iter().map(move |&j| self.nodes[j]))
+    }
+
+    fn has_node(&self, node: N) -> bool {
+        self.index_of(&node).is_some()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn num_edges(&self) -> u64 {
+        self.targets.len() as u64 / 2
+    }
+
+    fn degree(&self, node: N) -> usize {
+        self.index_of(&node).map_or(0, |i| CsrGraph::degree(self, i))
+    }
+
+    fn has_edge(&self, from: N, to: N) -> bool {
+        match (self.index_of(&from), self.index_of(&to)) {
+            // each neighbour run is sorted
+            (Some(i), Some(j)) => CsrGraph::neighbors(self, i).binary_search(&j).is_ok(),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! The degree distribution: sequence, CCDF and histograms.
+//!
+//! Heavy-tailed distributions are best read off the complementary CDF
+//! `P(K >= k)` on log-log axes, which needs no binning. The histograms are
+//! for the plots that do bin: [`DegreeDistribution::histogram`] with bins of
+//! a fixed width, and [`DegreeDistribution::log_histogram`] with bins whose
+//! edges grow geometrically, normalized by width so the tail of a power law
+//! stays a straight line.
+
+use crate::error::{Error, Result};
+use crate::output::{Field, Row};
+
+/// The degrees of every node of a network, sorted.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DegreeDistribution {
+    degrees: Vec<usize>,
+}
+
+/// One point of the degree CCDF.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CcdfPoint {
+    pub degree: usize,
+    /// Fraction of nodes with degree at least `degree`
+    pub fraction: f64,
+}
+
+/// One histogram bin, covering the degrees `lower..upper`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DegreeBin {
+    pub lower: usize,
+    pub upper: usize,
+    pub count: usize,
+    /// `count` over the number of nodes and the bin width, so densities of
+    /// bins of different widths are comparable
+    pub density: f64,
+}
+
+impl DegreeDistribution {
+    pub fn new(mut degrees: Vec<usize>) -> Self {
+        degrees.sort_unstable();
+        Self { degrees }
+    }
+
+    /// The degree sequence, in increasing order
+    pub fn sequence(&self) -> &[usize] {
+        &self.degrees
+    }
+
+    pub fn max(&self) -> usize {
+        self.degrees.last().copied().unwrap_or(0)
+    }
+
+    /// `P(K >= k)` at each degree `k` present, in increasing order
+    pub fn ccdf(&self) -> Vec<CcdfPoint> {
+        let n = self.degrees.len() as f64;
+        let mut points: Vec<CcdfPoint> = vec![];
+        for (i, &degree) in self.degrees.iter().enumerate() {
+            if points.last().is_none_or(|point| point.degree != degree) {
+                points.push(CcdfPoint { degree, fraction: (self.degrees.len() - i) as f64 / n });
+            }
+        }
+        points
+    }
+
+    /// Bins `0..width`, `width..2 width`, ... up to the maximum degree,
+    /// including the empty ones. The width must be positive.
+    pub fn histogram(&self, width: usize) -> Result<Vec<DegreeBin>> {
+        if width == 0 {
+            return Err(Error::InvalidParameter("bin width must be positive".to_owned()));
+        }
+        let edges: Vec<usize> = (0..=self.max() / width + 1).map(|i| i * width).collect();
+        Ok(self.bin(&edges))
+    }
+
+    /// Bins `[1, b)`, `[b, b^2)`, ... up to the maximum degree, with their
+    /// edges rounded up to integers (so the first few may merge). Degree 0
+    /// nodes are left out; they have no place on a log scale. The base must
+    /// be finite and greater than 1.
+    pub fn log_histogram(&self, base: f64) -> Result<Vec<DegreeBin>> {
+        if !(base > 1.0 && base.is_finite()) {
+            return Err(Error::InvalidParameter(format!("log bin base must be finite and greater than 1, got {}", base)));
+        }
+        let mut edges = vec![1];
+        let mut edge = 1.0;
+        while *edges.last().unwrap() <= self.max() {
+            edge *= base;
+            let next = edge.ceil() as usize;
+            if next > *edges.last().unwrap() {
+                edges.push(next);
+            }
+        }
+        Ok(self.bin(&edges))
+    }
+
+    fn bin(&self, edges: &[usize]) -> Vec<DegreeBin> {
+        let n = self.degrees.len() as f64;
+        edges
+            .windows(2)
+            .map(|bin| {
+                let (lower, upper) = (bin[0], bin[1]);
+                let count = self.degrees.partition_point(|&k| k < upper) - self.degrees.partition_point(|&k| k < lower);
+                DegreeBin { lower, upper, count, density: count as f64 / (n * (upper - lower) as f64) }
+            })
+            .collect()
+    }
+}
+
+impl Row for CcdfPoint {
+    const COLUMNS: &'static [&'static str] = &["degree", "ccdf"];
+
+    fn fields(&self) -> Vec<Field> {
+        vec![self.degree.into(), self.fraction.into()]
+    }
+}
+
+impl Row for DegreeBin {
+    const COLUMNS: &'static [&'static str] = &["lower", "upper", "count", "density"];
+
+    fn fields(&self) -> Vec<Field> {
+        vec![self.lower.into(), self.upper.into(), self.count.into(), self.density.into()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ccdf_and_bins_count_every_node() -> Result<()> {
+        let distribution = DegreeDistribution::new(vec![3, 1, 0, 1, 2, 9, 1, 4]);
+        assert_eq!(distribution.sequence(), &[0, 1, 1, 1, 2, 3, 4, 9]);
+        let ccdf: Vec<(usize, f64)> = distribution.ccdf().iter().map(|p| (p.degree, p.fraction)).collect();
+        assert_eq!(ccdf, vec![(0, 1.0), (1, 0.875), (2, 0.5), (3, 0.375), (4, 0.25), (9, 0.125)]);
+
+        let linear = distribution.histogram(4)?;
+        assert_eq!(linear.iter().map(|b| (b.lower, b.count)).collect::<Vec<_>>(), vec![(0, 6), (4, 1), (8, 1)]);
+        assert_eq!(linear[0].density, 6.0 / 32.0);
+
+        // edges 1, 2, 4, 8, 16
+        let log = distribution.log_histogram(2.0)?;
+        assert_eq!(log.iter().map(|b| (b.lower, b.upper, b.count)).collect::<Vec<_>>(), vec![(1, 2, 3), (2, 4, 2), (4, 8, 1), (8, 16, 1)]);
+        assert_eq!(log[3].density, 1.0 / 64.0);
+
+        assert!(matches!(distribution.histogram(0), Err(Error::InvalidParameter(_))));
+        assert!(matches!(distribution.log_histogram(1.0), Err(Error::InvalidParameter(_))));
+        Ok(())
+    }
+}
@@ -0,0 +1,242 @@
+//! Distance statistics that avoid a search from every node.
+//!
+//! [`ifub_diameter`] finds the exact diameter of a component with the iFUB
+//! algorithm (Crescenzi et al. 2013): it walks the levels of one BFS tree from
+//! the bottom up, searching only from the nodes in each level, and stops as
+//! soon as the lower bound from those searches meets the upper bound implied
+//! by the level. On real networks that is usually a handful of searches.
+//!
+//! [`hyper_anf`] estimates the neighbourhood function `N(t)` (the number of
+//! ordered pairs within distance `t`) with HyperANF (Boldi, Rosa & Vigna
+//! 2011). Every node keeps a HyperLogLog counter of the nodes within `t`
+//! hops, and a step unions each counter with its neighbours'. The distance
+//! distribution, mean geodesic distance and effective diameter all follow
+//! from `N(t)`, in time close to linear in the number of edges.
+
+use rayon::prelude::*;
+
+use crate::bfs::BfsWorkspace;
+use crate::csr::CsrGraph;
+use crate::node::Node;
+
+/// The result of [`ifub_diameter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Diameter {
+    pub diameter: u32,
+    /// Number of breadth-first searches it took
+    pub searches: usize,
+}
+
+/// Exact diameter of the component containing node index `source`.
+///
+/// `source` should be central for the bounds to close quickly; the highest
+/// degree node is the usual choice.
+pub fn ifub_diameter<N: Node>(graph: &CsrGraph<N>, source: usize) -> Diameter {
+    let mut ws = BfsWorkspace::new(graph.num_nodes());
+    let mut searches = 0;
+    let mut eccentricity = |ws: &mut BfsWorkspace, node: usize| {
+        searches += 1;
+        let last = *ws.run(graph, node).last().unwrap_or(&node);
+        (ws.distances()[last], last)
+    };
+
+    // double sweep: the far end of a search from the far end of `source` gives
+    // a good lower bound to start from
+    let (_, far) = eccentricity(&mut ws, source);
+    let (mut lower, _) = eccentricity(&mut ws, far);
+
+    let (ecc_source, _) = eccentricity(&mut ws, source);
+    let mut levels = vec![vec![]; ecc_source as usize + 1];
+    for &node in ws.reached() {
+        levels[ws.distances()[node] as usize].push(node);
+    }
+    lower = lower.max(ecc_source);
+
+    // two nodes at level <= i are at most 2i apart, so once the lower bound
+    // reaches 2i the levels still left cannot beat it
+    let mut level = ecc_source as usize;
+    while level > 0 && lower < 2 * level as u32 {
+        for &node in &levels[level] {
+            lower = lower.max(eccentricity(&mut ws, node).0);
+        }
+        level -= 1;
+    }
+    Diameter {
+        diameter: lower,
+        searches,
+    }
+}
+
+/// Estimated neighbourhood function of a network.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NeighbourhoodFunction {
+    /// `counts[t]` estimates the number of ordered pairs `(u, v)` with
+    /// `d(u, v) <= t`, including `u == v`; the last entry is the number of
+    /// reachable pairs
+    pub counts: Vec<f64>,
+}
+
+impl NeighbourhoodFunction {
+    /// Estimated fraction of reachable pairs (`u != v`) at each distance
+    /// `t >= 1`; entry 0 is for `t = 1`.
+    pub fn distance_distribution(&self) -> Vec<f64> {
+        let total = self.reachable_pairs();
+        self.counts
+            .windows(2)
+            .map(|w| if total > 0.0 { (w[1] - w[0]).max(0.0) / total } else { 0.0 })
+            .collect()
+    }
+
+    /// Estimated mean distance over all pairs of distinct nodes that are
+    /// connected, in every component.
+    pub fn mean_distance(&self) -> f64 {
+        self.distance_distribution()
+            .iter()
+            .enumerate()
+            .map(|(t, p)| (t + 1) as f64 * p)
+            .sum()
+    }
+
+    /// Smallest (interpolated) `t` within which a fraction `q` of the
+    /// reachable pairs are found; `q = 0.9` is the usual effective diameter.
+    pub fn effective_diameter(&self, q: f64) -> f64 {
+        let Some(&self_pairs) = self.counts.first() else {
+            return 0.0;
+        };
+        let target = q * self.reachable_pairs();
+        for t in 1..self.counts.len() {
+            let (before, after) = (self.counts[t - 1] - self_pairs, self.counts[t] - self_pairs);
+            if after >= target {
+                let step = after - before;
+                let fraction = if step > 0.0 { (target - before) / step } else { 1.0 };
+                return (t - 1) as f64 + fraction.clamp(0.0, 1.0);
+            }
+        }
+        (self.counts.len() - 1) as f64
+    }
+
+    fn reachable_pairs(&self) -> f64 {
+        match (self.counts.first(), self.counts.last()) {
+            (Some(first), Some(last)) => last - first,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Runs HyperANF with `2^log2m` registers per counter until no counter
+/// changes.
+///
+/// The relative standard error of each counter is about `1.04 / sqrt(2^log2m)`;
+/// the sums in `N(t)` are considerably more accurate than that. Memory is
+/// `2^log2m` bytes per node, twice over. `seed` picks the hash function.
+pub fn hyper_anf<N: Node>(graph: &CsrGraph<N>, log2m: u32, seed: u64) -> NeighbourhoodFunction {
+    let log2m = log2m.clamp(4, 16);
+    let m = 1usize << log2m;
+    let n = graph.num_nodes();
+
+    let mut current = vec![0u8; n * m];
+    for (node, registers) in current.chunks_mut(m).enumerate() {
+        let hash = splitmix64(node as u64 ^ seed);
+        let register = (hash as usize) & (m - 1);
+        let rest = hash >> log2m;
+        registers[register] = (rest.trailing_zeros().min(64 - log2m) + 1) as u8;
+    }
+    let mut next = current.clone();
+
+    let mut counts = vec![current.chunks(m).map(estimate).sum()];
+    loop {
+        let (changed, total) = next
+            .par_chunks_mut(m)
+            .enumerate()
+            .map(|(node, registers)| {
+                registers.copy_from_slice(&current[node * m..(node + 1) * m]);
+                let mut changed = false;
+                for &nbr in graph.neighbors(node) {
+                    let theirs = &current[nbr * m..(nbr + 1) * m];
+                    for (mine, &their) in registers.iter_mut().zip(theirs) {
+                        if their > *mine {
+                            *mine = their;
+                            changed = true;
+                        }
+                    }
+                }
+                (changed, estimate(registers))
+            })
+            .reduce(|| (false, 0.0), |(c1, t1), (c2, t2)| (c1 || c2, t1 + t2));
+        if !changed {
+            break;
+        }
+        // counters only grow, so the estimate should too
+        counts.push(total.max(*counts.last().unwrap_or(&0.0)));
+        std::mem::swap(&mut current, &mut next);
+    }
+    NeighbourhoodFunction { counts }
+}
+
+/// HyperLogLog cardinality estimate, with the linear-counting correction
+/// for small counts.
+fn estimate(registers: &[u8]) -> f64 {
+    let m = registers.len() as f64;
+    let alpha = match registers.len() {
+        16 => 0.673,
+        32 => 0.697,
+        64 => 0.709,
+        _ => 0.7213 / (1.0 + 1.079 / m),
+    };
+    let sum: f64 = registers.iter().map(|&r| (-(r as f64)).exp2()).sum();
+    let raw = alpha * m * m / sum;
+    let zeros = registers.iter().filter(|&&r| r == 0).count();
+    if raw <= 2.5 * m && zeros > 0 {
+        m * (m / zeros as f64).ln()
+    } else {
+        raw
+    }
+}
+
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::Analysis;
+    use crate::SimpleNetwork;
+
+    fn grid(rows: u64, cols: u64) -> crate::Result<CsrGraph<u64>> {
+        let node = |r: u64, c: u64| r * cols + c;
+        let across = (0..rows).flat_map(|r| (1..cols).map(move |c| (node(r, c - 1), node(r, c))));
+        let down = (1..rows).flat_map(|r| (0..cols).map(move |c| (node(r - 1, c), node(r, c))));
+        let network = SimpleNetwork::from_edges((0..rows * cols).collect(), across.chain(down))?;
+        Ok(CsrGraph::from_network(&network))
+    }
+
+    #[test]
+    fn ifub_matches_all_pairs() -> crate::Result<()> {
+        let graph = grid(5, 8)?;
+        let result = ifub_diameter(&graph, 20);
+        assert_eq!(result.diameter, 11);
+        assert!(result.searches < graph.num_nodes());
+
+        let (_, diameter, _) = graph.mgd_diameter();
+        assert_eq!(result.diameter as u64, diameter);
+        Ok(())
+    }
+
+    #[test]
+    fn hyper_anf_estimates_distances() -> crate::Result<()> {
+        let graph = grid(6, 6)?;
+        let (mgd, diameter, _) = graph.mgd_diameter();
+        let anf = hyper_anf(&graph, 10, 1);
+
+        assert_eq!(anf.counts.len() as u64, diameter + 1);
+        assert!((anf.counts[0] - 36.0).abs() < 2.0);
+        assert!((anf.mean_distance() - mgd).abs() / mgd < 0.1);
+        let effective = anf.effective_diameter(0.9);
+        assert!(effective > 5.0 && effective <= diameter as f64);
+        Ok(())
+    }
+}
@@ -0,0 +1,338 @@
+//! Significance of network statistics against random null models.
+//!
+//! An observed statistic means little until it is set against networks that
+//! share some of the observed network's features but are otherwise random.
+//! An [`Ensemble`] draws such networks, on the same nodes, from a
+//! [`NullModel`]:
+//!
+//! - [`NullModel::EdgeSwap`] keeps every degree exactly (the configuration
+//!   model), by running a chain of double edge swaps;
+//! - [`NullModel::Gnm`] keeps only the number of edges (Erdős–Rényi
+//!   `G(n, m)`);
+//! - [`NullModel::ChungLu`] keeps the degrees in expectation, joining each
+//!   pair independently with probability `min(1, k_i k_j / 2m)`. Self loops
+//!   are not drawn, so high degree nodes come out slightly short.
+//!
+//! [`test_statistic`] and [`test_node_statistic`] evaluate a statistic on
+//! the observed network and on `reps` draws, and summarize where the
+//! observed value falls in the null distribution as a [`Significance`].
+
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::error::{Error, Result};
+use crate::network::SimpleNetwork;
+use crate::node::Node;
+use crate::output::{Field, Row};
+
+/// Quantiles of the null distribution reported in [`Significance`].
+pub const QUANTILES: [f64; 5] = [0.025, 0.25, 0.5, 0.75, 0.975];
+
+/// A random graph model to test against.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NullModel {
+    /// Double edge swaps from the observed network: `burn_in` rounds of `m`
+    /// swaps before the first draw, then `spacing` rounds between draws
+    EdgeSwap { burn_in: u64, spacing: u64 },
+    /// Uniformly random graphs with the observed number of edges
+    Gnm,
+    /// Independent edges with probability `min(1, k_i k_j / 2m)`, from the
+    /// observed degrees `k`
+    ChungLu,
+}
+
+/// Draws null networks on the nodes of an observed network.
+#[derive(Debug, Clone)]
+pub struct Ensemble<N: Node = u64> {
+    model: NullModel,
+    /// The last draw; for edge swaps, the current state of the chain
+    current: SimpleNetwork<N>,
+    /// The observed nodes, sorted, with their degrees
+    nodes: Vec<(N, usize)>,
+    edges: u64,
+    burnt_in: bool,
+}
+
+impl<N: Node> Ensemble<N> {
+    pub fn new(network: &SimpleNetwork<N>, model: NullModel) -> Self {
+        let mut nodes: Vec<(N, usize)> = network.nodes().map(|node| (node, network.degree(node))).collect();
+        nodes.sort();
+        Self {
+            model,
+            current: network.clone(),
+            nodes,
+            edges: network.num_edges(),
+            burnt_in: false,
+        }
+    }
+
+    /// The next null network.
+    ///
+    /// Edge-swap draws come from one chain, so consecutive draws are
+    /// correlated unless `spacing` is large enough; the other models draw
+    /// independently. Fails if the network has no valid edge swap, e.g. a
+    /// star.
+    pub fn sample<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Result<&SimpleNetwork<N>> {
+        match self.model {
+            NullModel::EdgeSwap { burn_in, spacing } => {
+                let rounds = if self.burnt_in { spacing } else { burn_in };
+                self.burnt_in = true;
+                for _ in 0..rounds * self.edges {
+                    self.current.double_edge_swap_with_rng(rng)?;
+                }
+            }
+            NullModel::Gnm => self.current = self.gnm(rng),
+            NullModel::ChungLu => self.current = self.chung_lu(rng),
+        }
+        Ok(&self.current)
+    }
+
+    fn empty(&self) -> SimpleNetwork<N> {
+        SimpleNetwork::from_node_vec(self.nodes.iter().map(|&(node, _)| node).collect())
+    }
+
+    /// `m` distinct pairs drawn uniformly, by rejection
+    fn gnm<R: Rng + ?Sized>(&self, rng: &mut R) -> SimpleNetwork<N> {
+        let mut network = self.empty();
+        let n = self.nodes.len();
+        while network.num_edges() < self.edges {
+            let (a, b) = (self.nodes[rng.gen_range(0..n)].0, self.nodes[rng.gen_range(0..n)].0);
+            // self loops and repeated pairs are rejected
+            let _ = network.add_edge(a, b);
+        }
+        network
+    }
+
+    /// Chung-Lu graph in O(n + m) time, after Miller and Hagberg (2011):
+    /// with the nodes in decreasing order of degree, the edge probability
+    /// only falls along each row, so the candidates to try are found by
+    /// geometric skips at the current probability and then thinned.
+    fn chung_lu<R: Rng + ?Sized>(&self, rng: &mut R) -> SimpleNetwork<N> {
+        let mut network = self.empty();
+        if self.edges == 0 {
+            return network;
+        }
+        let mut order: Vec<(N, usize)> = self.nodes.clone();
+        order.sort_by_key(|&(node, degree)| (std::cmp::Reverse(degree), node));
+        let total = 2.0 * self.edges as f64;
+        let probability = |u: usize, v: usize| (order[u].1 as f64 * order[v].1 as f64 / total).min(1.0);
+        for u in 0..order.len() {
+            let mut v = u + 1;
+            let mut p = if v < order.len() { probability(u, v) } else { 0.0 };
+            while v < order.len() && p > 0.0 {
+                if p < 1.0 {
+                    let r: f64 = rng.gen();
+                    v = v.saturating_add(((1.0 - r).ln() / (1.0 - p).ln()).floor() as usize);
+                }
+                if v < order.len() {
+                    let q = probability(u, v);
+                    if rng.gen::<f64>() < q / p {
+                        network.add_edge(order[u].0, order[v].0).expect("each pair is tried once");
+                    }
+                    p = q;
+                    v += 1;
+                }
+            }
+        }
+        network
+    }
+}
+
+/// Where an observed value falls in a null distribution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Significance {
+    pub observed: f64,
+    /// Mean of the null samples
+    pub mean: f64,
+    /// Standard deviation of the null samples
+    pub sd: f64,
+    /// `(observed - mean) / sd`; infinite or NaN if the samples are all equal
+    pub z: f64,
+    /// Empirical two-sided p-value: twice the smaller tail, counting the
+    /// observed value among the samples so it is never 0
+    pub p: f64,
+    /// The null distribution at each of [`QUANTILES`]
+    pub quantiles: Vec<f64>,
+    pub samples: usize,
+}
+
+impl Significance {
+    /// Summarizes `samples` against `observed`; there must be at least one
+    pub fn from_samples(observed: f64, samples: &[f64]) -> Result<Self> {
+        if samples.is_empty() {
+            return Err(Error::InvalidParameter("a null distribution needs at least one sample".to_owned()));
+        }
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        let sd = (samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0).max(1.0)).sqrt();
+        let above = samples.iter().filter(|&&x| x >= observed).count();
+        let below = samples.iter().filter(|&&x| x <= observed).count();
+        let p = (2.0 * (above.min(below) + 1) as f64 / (n + 1.0)).min(1.0);
+
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        // linear interpolation between order statistics
+        let quantiles = QUANTILES
+            .iter()
+            .map(|q| {
+                let position = q * (n - 1.0);
+                let (lower, upper) = (position.floor() as usize, position.ceil() as usize);
+                sorted[lower] + (position - lower as f64) * (sorted[upper] - sorted[lower])
+            })
+            .collect();
+        Ok(Self {
+            observed,
+            mean,
+            sd,
+            z: (observed - mean) / sd,
+            p,
+            quantiles,
+            samples: samples.len(),
+        })
+    }
+}
+
+impl Row for Significance {
+    const COLUMNS: &'static [&'static str] =
+        &["observed", "null_mean", "null_sd", "z", "p", "q025", "q250", "q500", "q750", "q975", "samples"];
+
+    fn fields(&self) -> Vec<Field> {
+        let mut fields: Vec<Field> = vec![
+            self.observed.into(),
+            self.mean.into(),
+            self.sd.into(),
+            self.z.into(),
+            self.p.into(),
+        ];
+        fields.extend(self.quantiles.iter().map(|&q| Field::from(q)));
+        fields.push(self.samples.into());
+        fields
+    }
+}
+
+/// Tests a whole-network statistic against `reps` draws from `model`, at
+/// least one.
+pub fn test_statistic<N, F, R>(network: &SimpleNetwork<N>, model: NullModel, reps: usize, rng: &mut R, mut statistic: F) -> Result<Significance>
+where
+    N: Node,
+    F: FnMut(&SimpleNetwork<N>) -> f64,
+    R: Rng + ?Sized,
+{
+    if reps == 0 {
+        return Err(Error::InvalidParameter("a test needs at least one null sample".to_owned()));
+    }
+    let observed = statistic(network);
+    let mut ensemble = Ensemble::new(network, model);
+    let samples = (0..reps).map(|_| Ok(statistic(ensemble.sample(rng)?))).collect::<Result<Vec<f64>>>()?;
+    Significance::from_samples(observed, &samples)
+}
+
+/// Tests a per-node statistic against `reps` draws from `model` (at least
+/// one), node by node. Nodes the statistic leaves out of the observed
+/// network are not tested; those it leaves out of a draw just have one
+/// sample fewer.
+pub fn test_node_statistic<N, F, R>(
+    network: &SimpleNetwork<N>,
+    model: NullModel,
+    reps: usize,
+    rng: &mut R,
+    mut statistic: F,
+) -> Result<HashMap<N, Significance>>
+where
+    N: Node,
+    F: FnMut(&SimpleNetwork<N>) -> HashMap<N, f64>,
+    R: Rng + ?Sized,
+{
+    if reps == 0 {
+        return Err(Error::InvalidParameter("a test needs at least one null sample".to_owned()));
+    }
+    let observed = statistic(network);
+    let mut samples: HashMap<N, Vec<f64>> = observed.keys().map(|&node| (node, Vec::with_capacity(reps))).collect();
+    let mut ensemble = Ensemble::new(network, model);
+    for _ in 0..reps {
+        for (node, value) in statistic(ensemble.sample(rng)?) {
+            if let Some(values) = samples.get_mut(&node) {
+                values.push(value);
+            }
+        }
+    }
+    observed
+        .into_iter()
+        .filter(|(node, _)| !samples[node].is_empty())
+        .map(|(node, value)| Ok((node, Significance::from_samples(value, &samples[&node])?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::Analysis;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn null_models_keep_what_they_promise() -> crate::Result<()> {
+        // two triangles joined by an edge, and a square
+        let edges = [(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (2, 3), (6, 7), (7, 8), (8, 9), (9, 6)];
+        let network = SimpleNetwork::from_edges((0..10u64).collect(), edges)?;
+        let mut rng = ChaCha8Rng::seed_from_u64(8);
+
+        let mut swaps = Ensemble::new(&network, NullModel::EdgeSwap { burn_in: 5, spacing: 1 });
+        let sample = swaps.sample(&mut rng)?;
+        assert!(network.nodes().all(|node| sample.degree(node) == network.degree(node)));
+        let sample = Ensemble::new(&network, NullModel::Gnm).sample(&mut rng)?.clone();
+        assert_eq!((sample.num_nodes(), sample.num_edges()), (10, 11));
+        sample.validate()?;
+        // Chung-Lu has sum_{i<j} k_i k_j / 2m edges on average, which falls
+        // short of m by the self loops it leaves out
+        let mut chung_lu = Ensemble::new(&network, NullModel::ChungLu);
+        let mut total = 0;
+        for _ in 0..2000 {
+            total += chung_lu.sample(&mut rng)?.num_edges();
+        }
+        assert!((total as f64 / 2000.0 - (22.0 * 22.0 - 50.0) / 44.0).abs() < 0.3);
+
+        // ten triangles are far more clustered than G(n, m)
+        let edges = (0..30).step_by(3).flat_map(|t| [(t, t + 1), (t + 1, t + 2), (t + 2, t)]);
+        let triangles = SimpleNetwork::from_edges((0..30u64).collect(), edges)?;
+        let clustering = test_statistic(&triangles, NullModel::Gnm, 200, &mut rng, |g| g.cluster_coeff())?;
+        assert!(clustering.z > 2.0 && clustering.p < 0.05);
+        assert_eq!(clustering.samples, 200);
+        assert!(clustering.quantiles.windows(2).all(|q| q[0] <= q[1]));
+
+        // degree is fixed by edge swaps, so no node's degree is surprising
+        let degrees = test_node_statistic(&network, NullModel::EdgeSwap { burn_in: 2, spacing: 1 }, 20, &mut rng, |g| {
+            g.nodes().map(|node| (node, g.degree(node) as f64)).collect()
+        })?;
+        assert!(degrees.values().all(|d| d.sd == 0.0 && d.p == 1.0));
+
+        // there is nothing to test against without samples, and nothing to
+        // draw without edges
+        assert!(matches!(test_statistic(&network, NullModel::Gnm, 0, &mut rng, |g| g.cluster_coeff()), Err(Error::InvalidParameter(_))));
+        let edgeless = SimpleNetwork::from_node_vec((0..5u64).collect());
+        assert_eq!(Ensemble::new(&edgeless, NullModel::ChungLu).sample(&mut rng)?.num_edges(), 0);
+
+        // a star has no valid double edge swap, so there is nothing to draw
+        let star = SimpleNetwork::from_edges((0..5u64).collect(), (1..5).map(|leaf| (0, leaf)))?;
+        let swaps = NullModel::EdgeSwap { burn_in: 1, spacing: 1 };
+        assert!(matches!(Ensemble::new(&star, swaps).sample(&mut rng), Err(Error::InvalidParameter(_))));
+        assert!(matches!(test_statistic(&star, swaps, 2, &mut rng, |g| g.cluster_coeff()), Err(Error::InvalidParameter(_))));
+        let degrees = test_node_statistic(&star, swaps, 2, &mut rng, |g| g.nodes().map(|node| (node, g.degree(node) as f64)).collect());
+        assert!(matches!(degrees, Err(Error::InvalidParameter(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn empirical_p_and_quantiles() -> crate::Result<()> {
+        let samples: Vec<f64> = (1..=99).map(f64::from).collect();
+        let significance = Significance::from_samples(99.0, &samples)?;
+        assert_eq!(significance.mean, 50.0);
+        assert_eq!(significance.quantiles[2], 50.0);
+        assert_eq!(significance.quantiles[0], 1.0 + 0.025 * 98.0);
+        // one sample at least as large, plus the observed value
+        assert_eq!(significance.p, 2.0 * 2.0 / 100.0);
+        assert!(Significance::from_samples(1.0, &[]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+//! The error type returned throughout the library.
+//!
+//! Graph edits fail with the node or edge at fault and parsers with the line
+//! they stopped at, so a caller can tell the cases apart (and, say, skip a
+//! duplicate edge) without matching on message text. The binary wraps these
+//! in `anyhow` for reporting.
+
+use std::fmt;
+use std::io;
+
+/// `Result` with the library's [`Error`].
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A node, by id, that is not in the network
+    UnknownNode(u64),
+    /// An edge that is not in the network
+    MissingEdge { from: u64, to: u64 },
+    /// An edge from a node to itself, which a simple network cannot hold
+    SelfLoop(u64),
+    /// An edge that is already in the network
+    DuplicateEdge { from: u64, to: u64 },
+    /// Malformed input at a 1-based line number
+    Parse { line: usize, message: String },
+    /// An argument or setting outside what is accepted
+    InvalidParameter(String),
+    /// Input that parses but does not fit together, e.g. a corrupt snapshot
+    InvalidData(String),
+    /// An iterative method that had not converged after this many iterations
+    NoConvergence { iterations: usize },
+    Io(io::Error),
+    Csv(csv::Error),
+    Json(serde_json::Error),
+    Toml(toml::de::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnknownNode(node) => write!(f, "node {} is not in the network", node),
+            Error::MissingEdge { from, to } => write!(f, "edge {}-{} is not in the network", from, to),
+            Error::SelfLoop(node) => write!(f, "self loop on node {}", node),
+            Error::DuplicateEdge { from, to } => write!(f, "edge {}-{} is already in the network", from, to),
+            Error::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            Error::InvalidParameter(message) | Error::InvalidData(message) => f.write_str(message),
+            Error::NoConvergence { iterations } => write!(f, "did not converge within {} iterations", iterations),
+            Error::Io(e) => e.fmt(f),
+            Error::Csv(e) => e.fmt(f),
+            Error::Json(e) => e.fmt(f),
+            Error::Toml(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Csv(e) => Some(e),
+            Error::Json(e) => Some(e),
+            Error::Toml(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// Records that fail to deserialize become [`Error::Parse`] with their line.
+impl From<csv::Error> for Error {
+    fn from(e: csv::Error) -> Self {
+        let parsed = match e.kind() {
+            csv::ErrorKind::Deserialize { pos: Some(pos), err } => Some((pos.line(), err.to_string())),
+            csv::ErrorKind::UnequalLengths {
+                pos: Some(pos),
+                expected_len,
+                len,
+            } => Some((pos.line(), format!("expected {} fields, found {}", expected_len, len))),
+            _ => None,
+        };
+        match parsed {
+            Some((line, message)) => Error::Parse {
+                line: line as usize,
+                message,
+            },
+            None => Error::Csv(e),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Error::Toml(e)
+    }
+}
@@ -0,0 +1,251 @@
+//! Declarative experiment specifications.
+//!
+//! A spec is a TOML or JSON file listing experiments. Each experiment names an
+//! analysis (one of the CLI subcommands), the datasets to run it on, and its
+//! parameters. A parameter given as a list is swept: the spec is expanded into
+//! one [`RunConfig`] per point of the grid of all swept parameters.
+//!
+//! ```toml
+//! output_dir = "output/fb100"
+//!
+//! [[experiment]]
+//! name = "null-model"
+//! analysis = "null-model"
+//! replicates = 2
+//! datasets = [{ path = "data/Caltech36.txt", nodes = "data/Caltech36_attr.txt" }]
+//!
+//! [experiment.parameters]
+//! reps = 1000
+//! burn-in = [5, 10, 20]
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+
+/// A whole spec file.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExperimentSpec {
+    /// Where results go; overrides the command line's output directory
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+    #[serde(rename = "experiment")]
+    pub experiments: Vec<Experiment>,
+}
+
+/// One analysis over a set of datasets.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Experiment {
+    pub name: String,
+    /// The subcommand to run, e.g. `stats` or `sbm-fit`
+    pub analysis: String,
+    #[serde(default)]
+    pub datasets: Vec<Dataset>,
+    /// Input format, as for `--format`
+    #[serde(default)]
+    pub format: Option<String>,
+    #[serde(default)]
+    pub snapshot: bool,
+    /// How many times each point of the parameter grid is run
+    #[serde(default = "one")]
+    pub replicates: usize,
+    /// Parameters by their long option name; lists are swept
+    #[serde(default)]
+    pub parameters: BTreeMap<String, Value>,
+}
+
+fn one() -> usize {
+    1
+}
+
+/// An input network, with its node and attribute files if it has them.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Dataset {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub nodes: Option<PathBuf>,
+    #[serde(default)]
+    pub attrs: Option<PathBuf>,
+}
+
+/// A single point of an experiment's parameter grid.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunConfig {
+    pub experiment: String,
+    pub analysis: String,
+    pub datasets: Vec<Dataset>,
+    pub format: Option<String>,
+    pub snapshot: bool,
+    /// Index of this replicate, `0..replicates`
+    pub replicate: usize,
+    /// Every parameter with a single value
+    pub parameters: BTreeMap<String, Value>,
+}
+
+impl ExperimentSpec {
+    /// Parses a spec, as JSON if `path` ends in `.json` and as TOML otherwise.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = read_to_string(path)?;
+        if path.extension().is_some_and(|e| e == "json") {
+            Ok(serde_json::from_str(&text)?)
+        } else {
+            Self::from_toml(&text)
+        }
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    /// Expands every experiment into its runs, in spec order.
+    pub fn resolve(&self) -> Result<Vec<RunConfig>> {
+        let mut runs = vec![];
+        for experiment in &self.experiments {
+            runs.extend(experiment.resolve()?);
+        }
+        Ok(runs)
+    }
+}
+
+impl Experiment {
+    /// The cartesian product of the swept parameters, times the replicates.
+    ///
+    /// The last parameter (in name order) varies fastest.
+    pub fn resolve(&self) -> Result<Vec<RunConfig>> {
+        let mut grid = vec![BTreeMap::new()];
+        for (name, value) in &self.parameters {
+            let values = match value {
+                Value::Array(values) if values.is_empty() => {
+                    return Err(Error::InvalidParameter(format!(
+                        "experiment {}: parameter {} sweeps no values",
+                        self.name, name
+                    )))
+                }
+                Value::Array(values) => values.clone(),
+                value => vec![value.clone()],
+            };
+            if let Some(nested) = values.iter().find(|v| v.is_array() || v.is_object() || v.is_null()) {
+                return Err(Error::InvalidParameter(format!(
+                    "experiment {}: parameter {} has invalid value {}",
+                    self.name, name, nested
+                )));
+            }
+            grid = grid
+                .into_iter()
+                .flat_map(|point| {
+                    values.iter().map(move |value| {
+                        let mut point = point.clone();
+                        point.insert(name.clone(), value.clone());
+                        point
+                    })
+                })
+                .collect();
+        }
+
+        Ok(grid
+            .into_iter()
+            .flat_map(|parameters| {
+                (0..self.replicates).map(move |replicate| RunConfig {
+                    experiment: self.name.clone(),
+                    analysis: self.analysis.clone(),
+                    datasets: self.datasets.clone(),
+                    format: self.format.clone(),
+                    snapshot: self.snapshot,
+                    replicate,
+                    parameters: parameters.clone(),
+                })
+            })
+            .collect())
+    }
+}
+
+impl RunConfig {
+    /// The command line for this run, starting with the subcommand.
+    ///
+    /// Boolean parameters become flags, which are left out when `false`.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec![self.analysis.clone()];
+        args.extend(self.datasets.iter().map(|d| d.path.display().to_string()));
+        for dataset in &self.datasets {
+            if let Some(nodes) = &dataset.nodes {
+                args.extend(["--nodes".to_owned(), nodes.display().to_string()]);
+            }
+        }
+        for dataset in &self.datasets {
+            if let Some(attrs) = &dataset.attrs {
+                args.extend(["--attrs".to_owned(), attrs.display().to_string()]);
+            }
+        }
+        if let Some(format) = &self.format {
+            args.extend(["--format".to_owned(), format.clone()]);
+        }
+        if self.snapshot {
+            args.push("--snapshot".to_owned());
+        }
+        for (name, value) in &self.parameters {
+            let option = format!("--{}", name.replace('_', "-"));
+            match value {
+                Value::Bool(true) => args.push(option),
+                Value::Bool(false) => {}
+                Value::String(s) => args.extend([option, s.clone()]),
+                value => args.extend([option, value.to_string()]),
+            }
+        }
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parameter_grid_is_expanded() -> crate::Result<()> {
+        let spec = ExperimentSpec::from_toml(
+            r#"
+[[experiment]]
+name = "fit"
+analysis = "sbm-fit"
+replicates = 2
+datasets = [{ path = "data/zkc.txt" }]
+
+[experiment.parameters]
+groups = [2, 3]
+phases = 30
+diagnostics = false
+burn_in = ["a", "b", "c"]
+"#,
+        )?;
+
+        let runs = spec.resolve()?;
+        assert_eq!(runs.len(), 2 * 3 * 2);
+        assert_eq!(runs[0].parameters["groups"], 2);
+        assert_eq!(runs[1].replicate, 1);
+        assert_eq!(runs[2].parameters["groups"], 3);
+        assert_eq!(runs[4].parameters["burn_in"], "b");
+        assert_eq!(
+            runs[0].to_args(),
+            ["sbm-fit", "data/zkc.txt", "--burn-in", "a", "--groups", "2", "--phases", "30"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn json_specs_and_bad_sweeps() -> crate::Result<()> {
+        let spec: ExperimentSpec = serde_json::from_str(
+            r#"{"experiment": [{"name": "x", "analysis": "stats", "parameters": {"reps": []}}]}"#,
+        )?;
+        assert!(spec.resolve().is_err());
+        assert!(ExperimentSpec::from_toml("[[experiment]]\nname = \"x\"\nanalysis = \"stats\"\ntypo = 1\n").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,420 @@
+//! Loaders for common benchmark graph formats.
+//!
+//! Two formats are supported besides the FB100 files handled in [`crate::hw1`]:
+//!
+//! * SNAP-style edge lists: one edge per line, `#` or `%` comment lines, and any
+//!   run of whitespace, commas or semicolons between the two node ids. Extra
+//!   columns (weights, timestamps) are ignored.
+//! * Matrix Market coordinate files (`.mtx`) as distributed by SuiteSparse. Only
+//!   square matrices are accepted, and the value column (if any) is ignored.
+//!
+//! Edge lists with string node names can be read through an
+//! [`Interner`], which assigns each label a dense id.
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::hw1::Edge;
+use crate::interner::Interner;
+use crate::SimpleNetwork;
+
+/// How duplicate edges, self loops and edge direction are treated while loading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadOptions {
+    /// Treat `u v` and `v u` as different edges.
+    pub directed: bool,
+    /// Keep edges from a node to itself.
+    pub self_loops: bool,
+    /// Keep edges that appear more than once.
+    pub duplicates: bool,
+}
+
+impl Default for LoadOptions {
+    /// Undirected, with self loops and duplicates dropped - i.e. a simple graph.
+    fn default() -> Self {
+        Self {
+            directed: false,
+            self_loops: false,
+            duplicates: false,
+        }
+    }
+}
+
+/// A cleaned edge list, with the node set and a count of what was dropped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeList {
+    /// Every node id, sorted. Includes nodes that only appear in dropped edges,
+    /// and for Matrix Market files every row index even if it has no entries.
+    pub nodes: Vec<u64>,
+    pub edges: Vec<Edge>,
+    pub directed: bool,
+    pub self_loops_dropped: usize,
+    pub duplicates_dropped: usize,
+}
+
+impl EdgeList {
+    /// Builds an (undirected) `SimpleNetwork` from the edge list.
+    ///
+    /// Self loops are skipped since a `SimpleNetwork` cannot hold them, and
+    /// directed edges are symmetrized.
+    pub fn into_network(self) -> Result<SimpleNetwork<u64>> {
+        let mut network = SimpleNetwork::from_node_vec(self.nodes);
+        for edge in self.edges {
+            match network.add_edge(edge.from, edge.to) {
+                Ok(()) | Err(Error::SelfLoop(_)) | Err(Error::DuplicateEdge { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(network)
+    }
+}
+
+/// Applies [`LoadOptions`] to edges one at a time.
+///
+/// Shared by the loaders here and the streaming reader, so that both agree on
+/// what counts as a self loop. Dropping duplicates means remembering every
+/// edge kept, which is fine for the loaders (they keep the edges anyway) but
+/// not for a stream; see [`streaming`](Self::streaming).
+#[derive(Debug, Clone, Default)]
+pub struct EdgeFilter {
+    options: LoadOptions,
+    /// Edges kept so far, if duplicates are being dropped
+    seen: Option<HashSet<(u64, u64)>>,
+    pub self_loops_dropped: usize,
+    pub duplicates_dropped: usize,
+}
+
+impl EdgeFilter {
+    pub fn new(options: LoadOptions) -> Self {
+        Self {
+            options,
+            seen: (!options.duplicates).then(HashSet::new),
+            ..Default::default()
+        }
+    }
+
+    /// A filter that uses constant memory: it drops self loops as `options`
+    /// say, but passes duplicates through for the destination to reject
+    /// (e.g. [`SimpleNetwork::add_edge`] reports [`Error::DuplicateEdge`]).
+    pub fn streaming(options: LoadOptions) -> Self {
+        Self {
+            options,
+            ..Default::default()
+        }
+    }
+
+    /// Returns whether the edge should be kept, recording it if so.
+    pub fn accept(&mut self, from: u64, to: u64) -> bool {
+        if from == to && !self.options.self_loops {
+            self.self_loops_dropped += 1;
+            return false;
+        }
+        if let Some(seen) = &mut self.seen {
+            let key = if self.options.directed || from <= to {
+                (from, to)
+            } else {
+                (to, from)
+            };
+            if !seen.insert(key) {
+                self.duplicates_dropped += 1;
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Returns true for blank lines and lines starting with a comment marker.
+pub(crate) fn is_comment(line: &str) -> bool {
+    let line = line.trim_start();
+    line.is_empty() || line.starts_with('#') || line.starts_with('%') || line.starts_with("//")
+}
+
+/// Splits a data line on whitespace, commas or semicolons.
+pub(crate) fn split_fields(line: &str) -> impl Iterator<Item = &str> {
+    line.split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+        .filter(|field| !field.is_empty())
+}
+
+/// Reads the first two fields of a data line as node ids.
+pub(crate) fn parse_pair(line: &str, line_no: usize) -> Result<(u64, u64)> {
+    let mut fields = split_fields(line);
+    let mut next_id = || -> Result<u64> {
+        let field = fields
+            .next()
+            .ok_or_else(|| parse_error(line_no, "expected two node ids".into()))?;
+        field
+            .parse()
+            .map_err(|_| parse_error(line_no, format!("invalid node id `{}`", field)))
+    };
+    Ok((next_id()?, next_id()?))
+}
+
+fn parse_error(line: usize, message: String) -> Error {
+    Error::Parse { line, message }
+}
+
+/// Parse a SNAP-style edge list.
+///
+/// A first data line that does not start with two integers is taken to be a
+/// column header and skipped.
+pub fn parse_snap_edges<R: Read>(input: R, options: LoadOptions) -> Result<EdgeList> {
+    let mut filter = EdgeFilter::new(options);
+    let mut nodes = HashSet::new();
+    let mut edges = vec![];
+    let mut first = true;
+
+    for (i, line) in BufReader::new(input).lines().enumerate() {
+        let line = line?;
+        if is_comment(&line) {
+            continue;
+        }
+        let pair = parse_pair(&line, i + 1);
+        if first && pair.is_err() {
+            first = false;
+            continue;
+        }
+        first = false;
+
+        let (from, to) = pair?;
+        nodes.insert(from);
+        nodes.insert(to);
+        if filter.accept(from, to) {
+            edges.push(Edge { from, to });
+        }
+    }
+
+    let mut nodes: Vec<u64> = nodes.into_iter().collect();
+    nodes.sort();
+    Ok(EdgeList {
+        nodes,
+        edges,
+        directed: options.directed,
+        self_loops_dropped: filter.self_loops_dropped,
+        duplicates_dropped: filter.duplicates_dropped,
+    })
+}
+
+/// Parse an edge list whose nodes are arbitrary string labels.
+///
+/// Same layout as [`parse_snap_edges`], but the first two fields of every
+/// line are interned rather than parsed as numbers, so there is no header
+/// detection - comment out any header line.
+pub fn parse_labeled_edges<R: Read>(
+    input: R,
+    options: LoadOptions,
+    interner: &mut Interner,
+) -> Result<EdgeList> {
+    let mut filter = EdgeFilter::new(options);
+    let mut nodes = HashSet::new();
+    let mut edges = vec![];
+
+    for (i, line) in BufReader::new(input).lines().enumerate() {
+        let line = line?;
+        if is_comment(&line) {
+            continue;
+        }
+        let mut fields = split_fields(&line);
+        let (from, to) = match (fields.next(), fields.next()) {
+            (Some(from), Some(to)) => (interner.intern(from), interner.intern(to)),
+            _ => return Err(parse_error(i + 1, "expected two node labels".into())),
+        };
+        nodes.insert(from);
+        nodes.insert(to);
+        if filter.accept(from, to) {
+            edges.push(Edge { from, to });
+        }
+    }
+
+    let mut nodes: Vec<u64> = nodes.into_iter().collect();
+    nodes.sort();
+    Ok(EdgeList {
+        nodes,
+        edges,
+        directed: options.directed,
+        self_loops_dropped: filter.self_loops_dropped,
+        duplicates_dropped: filter.duplicates_dropped,
+    })
+}
+
+/// Parse a Matrix Market coordinate file.
+///
+/// Node ids are the 1-based row/column indices from the file. For `symmetric`,
+/// `skew-symmetric` and `hermitian` matrices each stored entry stands for both
+/// directions, so a directed load emits both.
+pub fn parse_matrix_market<R: Read>(input: R, options: LoadOptions) -> Result<EdgeList> {
+    let mut lines = BufReader::new(input).lines().enumerate();
+
+    let header = match lines.next() {
+        Some((_, line)) => line?,
+        None => return Err(parse_error(1, "empty Matrix Market file".into())),
+    };
+    let tokens: Vec<String> = header.split_whitespace().map(|t| t.to_lowercase()).collect();
+    if tokens.len() < 5 || tokens[0] != "%%matrixmarket" || tokens[1] != "matrix" {
+        return Err(parse_error(1, "missing %%MatrixMarket matrix header".into()));
+    }
+    if tokens[2] != "coordinate" {
+        return Err(parse_error(
+            1,
+            format!("only coordinate matrices are supported, found `{}`", tokens[2]),
+        ));
+    }
+    let symmetric = match tokens[4].as_str() {
+        "general" => false,
+        "symmetric" | "skew-symmetric" | "hermitian" => true,
+        other => return Err(parse_error(1, format!("unknown symmetry `{}`", other))),
+    };
+
+    // size line: rows cols entries
+    let mut size = None;
+    for (i, line) in lines.by_ref() {
+        let line = line?;
+        if is_comment(&line) {
+            continue;
+        }
+        let fields = split_fields(&line)
+            .map(|f| f.parse::<u64>().map_err(|_| parse_error(i + 1, format!("invalid size `{}`", f))))
+            .collect::<Result<Vec<u64>>>()?;
+        if fields.len() != 3 {
+            return Err(parse_error(i + 1, "expected `rows cols entries`".into()));
+        }
+        size = Some((fields[0], fields[1], fields[2]));
+        break;
+    }
+    let (rows, cols, entries) = size.ok_or(Error::InvalidData("missing Matrix Market size line".into()))?;
+    if rows != cols {
+        return Err(Error::InvalidData(format!(
+            "adjacency matrix must be square, found {}x{}",
+            rows, cols
+        )));
+    }
+
+    let mut filter = EdgeFilter::new(options);
+    let mut edges = vec![];
+    let mut read = 0;
+    for (i, line) in lines {
+        let line = line?;
+        if is_comment(&line) {
+            continue;
+        }
+        let (from, to) = parse_pair(&line, i + 1)?;
+        if from == 0 || to == 0 || from > rows || to > rows {
+            return Err(parse_error(
+                i + 1,
+                format!("entry ({}, {}) outside {}x{} matrix", from, to, rows, cols),
+            ));
+        }
+        read += 1;
+        if filter.accept(from, to) {
+            edges.push(Edge { from, to });
+        }
+        if symmetric && options.directed && from != to && filter.accept(to, from) {
+            edges.push(Edge { from: to, to: from });
+        }
+    }
+    if read != entries {
+        return Err(Error::InvalidData(format!("expected {} entries, found {}", entries, read)));
+    }
+
+    Ok(EdgeList {
+        nodes: (1..=rows).collect(),
+        edges,
+        directed: options.directed,
+        self_loops_dropped: filter.self_loops_dropped,
+        duplicates_dropped: filter.duplicates_dropped,
+    })
+}
+
+/// Load an edge list from a file, picking the parser from the contents.
+///
+/// Files starting with a `%%MatrixMarket` banner are read as Matrix Market,
+/// everything else as a SNAP edge list.
+pub fn load_edge_list<P: AsRef<Path>>(path: P, options: LoadOptions) -> Result<EdgeList> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let is_mtx = reader.fill_buf()?.starts_with(b"%%MatrixMarket");
+    if is_mtx {
+        parse_matrix_market(reader, options)
+    } else {
+        parse_snap_edges(reader, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snap_edges_skip_comments_and_clean_up() -> crate::Result<()> {
+        const DATA: &[u8] = b"# Directed graph: example
+# FromNodeId\tToNodeId
+1\t2
+2   1
+3,3
+2 ; 4 0.5
+";
+
+        let out = parse_snap_edges(DATA, LoadOptions::default())?;
+        assert_eq!(out.nodes, vec![1, 2, 3, 4]);
+        assert_eq!(out.edges, vec![Edge { from: 1, to: 2 }, Edge { from: 2, to: 4 }]);
+        assert_eq!((out.self_loops_dropped, out.duplicates_dropped), (1, 1));
+
+        let directed = LoadOptions {
+            directed: true,
+            ..Default::default()
+        };
+        let out = parse_snap_edges(DATA, directed)?;
+        assert_eq!(out.edges.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn labeled_edges_are_interned() -> crate::Result<()> {
+        const DATA: &[u8] = b"Medici Strozzi
+Strozzi,Medici
+Albizzi Medici
+";
+
+        let mut interner = Interner::new();
+        let out = parse_labeled_edges(DATA, LoadOptions::default(), &mut interner)?;
+        assert_eq!(out.nodes, vec![0, 1, 2]);
+        assert_eq!(out.edges, vec![Edge { from: 0, to: 1 }, Edge { from: 2, to: 0 }]);
+        assert_eq!(interner.label(2), Some("Albizzi"));
+        assert_eq!(interner.get("Strozzi"), Some(1));
+        Ok(())
+    }
+
+    #[test]
+    fn matrix_market_symmetric() -> crate::Result<()> {
+        const DATA: &[u8] = b"%%MatrixMarket matrix coordinate pattern symmetric
+% a comment
+4 4 3
+2 1
+3 1
+3 3
+";
+
+        let out = parse_matrix_market(DATA, LoadOptions::default())?;
+        assert_eq!(out.nodes, vec![1, 2, 3, 4]);
+        assert_eq!(out.edges, vec![Edge { from: 2, to: 1 }, Edge { from: 3, to: 1 }]);
+
+        let network = out.into_network()?;
+        assert_eq!(network.num_edges(), 2);
+        assert_eq!(network.num_nodes(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn matrix_market_rejects_bad_entries() {
+        const DATA: &[u8] = b"%%MatrixMarket matrix coordinate real general
+2 2 1
+3 1 1.0
+";
+        assert!(matches!(
+            parse_matrix_market(DATA, LoadOptions::default()),
+            Err(Error::Parse { line: 3, .. })
+        ));
+    }
+}
@@ -0,0 +1,84 @@
+//! A read-only interface over network representations.
+//!
+//! Algorithms that only look at a network take a [`Graph`] rather than a
+//! concrete type, so the same code runs on a
+//! [`SimpleNetwork`](crate::SimpleNetwork), a [`CsrGraph`](crate::csr::CsrGraph)
+//! or a view of either. Only `nodes`, `neighbors` and `has_node` have to be
+//! provided; the rest have default implementations in terms of those, which
+//! an implementation can replace with something faster.
+
+use crate::node::Node;
+
+/// An undirected, simple graph that can be queried but not changed.
+pub trait Graph {
+    type Node: Node;
+
+    /// Every node, in no particular order
+    fn nodes(&self) -> impl Iterator<Item = Self::Node> + '_;
+
+    /// The neighbours of `node`; none if it is not in the graph
+    fn neighbors(&self, node: Self::Node) -> impl Iterator<Item = Self::Node> + '_;
+
+    fn has_node(&self, node: Self::Node) -> bool;
+
+    fn num_nodes(&self) -> usize {
+        self.nodes().count()
+    }
+
+    /// Number of (undirected) edges
+    fn num_edges(&self) -> u64 {
+        self.nodes().map(|node| self.degree(node) as u64).sum::<u64>() / 2
+    }
+
+    /// Number of neighbours of `node`; 0 if it is not in the graph
+    fn degree(&self, node: Self::Node) -> usize {
+        self.neighbors(node).count()
+    }
+
+    fn has_edge(&self, from: Self::Node, to: Self::Node) -> bool {
+        self.neighbors(from).any(|node| node == to)
+    }
+
+    /// Every edge once, as `(u, v)` with `u < v`
+    fn edges(&self) -> impl Iterator<Item = (Self::Node, Self::Node)> + '_ {
+        self.nodes()
+            .flat_map(move |u| self.neighbors(u).filter(move |&v| u < v).map(move |v| (u, v)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csr::CsrGraph;
+    use crate::SimpleNetwork;
+
+    fn degrees<G: Graph>(graph: &G) -> Vec<(G::Node, usize)> {
+        let mut degrees: Vec<_> = graph.nodes().map(|node| (node, graph.degree(node))).collect();
+        degrees.sort();
+        degrees
+    }
+
+    #[test]
+    fn network_and_csr_agree() -> crate::Result<()> {
+        let network = SimpleNetwork::from_edges(vec![1u64, 2, 3, 4], [(1, 2), (2, 3), (3, 1)])?;
+        let csr = CsrGraph::from_network(&network);
+
+        assert_eq!(degrees(&network), degrees(&csr));
+        assert_eq!(degrees(&csr), vec![(1, 2), (2, 2), (3, 2), (4, 0)]);
+        assert_eq!((Graph::num_edges(&csr), network.num_edges()), (3, 3));
+        assert!(csr.has_edge(3, 1) && !csr.has_edge(1, 4) && !csr.has_edge(9, 1));
+        assert!(!Graph::has_node(&csr, 9) && Graph::neighbors(&csr, 9).next().is_none());
+
+        let mut edges: Vec<_> = csr.edges().collect();
+        edges.sort();
+        assert_eq!(edges, vec![(1, 2), (1, 3), (2, 3)]);
+
+        let back = csr.to_network()?;
+        back.validate()?;
+        assert_eq!(back.num_edges(), 3);
+        // 0 lists 1 twice
+        let repeated = CsrGraph::from_parts(vec![1u64, 2], vec![0, 2, 4], vec![1, 1, 0, 0])?;
+        assert!(matches!(repeated.to_network(), Err(crate::Error::DuplicateEdge { .. })));
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+//! Attribute assortativity: do nodes link to others with the same attribute?
+//!
+//! [`AttributeMixing`] counts the edges between each pair of attribute values
+//! with [`compute_mixing_matrix`], treating each value as a group. Normalized,
+//! this is the `e_rs` matrix of Newman (2003) (and of `math_hw4.m`), whose
+//! rows sum to `a_r`, the fraction of edge ends at nodes with value `r`. From
+//! it follow the categorical assortativity coefficient
+//! `r = (sum_r e_rr - sum_r a_r^2) / (1 - sum_r a_r^2)`, the modularity
+//! `Q = sum_r (e_rr - a_r^2)` of the partition by value, and, for ordered
+//! values such as the class year, the Pearson correlation of the values at
+//! either end of an edge.
+//!
+//! Nodes without a value (0 in the FB100 files) are left out, along with
+//! their edges.
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::graph::Graph;
+use crate::hw1::NodeData;
+use crate::output::{Field, Row};
+use crate::partition::{compute_mixing_matrix, Partition};
+
+/// Edge counts between the values of one node attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeMixing {
+    /// The values present, sorted; they index the matrix
+    pub values: Vec<i64>,
+    /// Edge ends from a node with value `values[r]` to one with `values[s]`.
+    /// Each edge is counted from both ends, so this is symmetric
+    pub counts: Vec<Vec<usize>>,
+    /// Number of nodes that have a value
+    pub nodes: usize,
+}
+
+impl AttributeMixing {
+    /// Mixing of `attribute` over `graph`; nodes missing from `attribute` are
+    /// ignored.
+    pub fn new<G: Graph>(graph: &G, attribute: &HashMap<G::Node, i64>) -> Self {
+        let values: Vec<i64> = graph
+            .nodes()
+            .filter_map(|node| attribute.get(&node).copied())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let partition: Partition<G::Node> = graph
+            .nodes()
+            .filter_map(|node| {
+                let value = attribute.get(&node)?;
+                Some((node, (values.binary_search(value).ok()?, false)))
+            })
+            .collect();
+        Self {
+            counts: compute_mixing_matrix(graph, values.len(), &partition),
+            nodes: partition.len(),
+            values,
+        }
+    }
+
+    /// Number of edges with a value at both ends
+    pub fn edges(&self) -> usize {
+        self.total() / 2
+    }
+
+    fn total(&self) -> usize {
+        self.counts.iter().flatten().sum()
+    }
+
+    /// The normalized mixing matrix `e_rs`, summing to 1
+    pub fn normalized(&self) -> Vec<Vec<f64>> {
+        let total = self.total() as f64;
+        self.counts
+            .iter()
+            .map(|row| row.iter().map(|&count| count as f64 / total).collect())
+            .collect()
+    }
+
+    /// `a_r`, the fraction of edge ends at nodes with each value
+    pub fn marginals(&self) -> Vec<f64> {
+        self.normalized().iter().map(|row| row.iter().sum()).collect()
+    }
+
+    /// Modularity of the partition of the nodes by value
+    pub fn modularity(&self) -> f64 {
+        let e = self.normalized();
+        self.marginals().iter().enumerate().map(|(r, a)| e[r][r] - a * a).sum()
+    }
+
+    /// Categorical assortativity coefficient: 1 if every edge joins equal
+    /// values, 0 if values mix at random. NaN with fewer than two values.
+    pub fn assortativity(&self) -> f64 {
+        let e = self.normalized();
+        let same: f64 = (0..e.len()).map(|r| e[r][r]).sum();
+        let random: f64 = self.marginals().iter().map(|a| a * a).sum();
+        (same - random) / (1.0 - random)
+    }
+
+    /// Pearson correlation of the values at either end of an edge, for
+    /// attributes where the values are ordered
+    pub fn numeric_assortativity(&self) -> f64 {
+        let e = self.normalized();
+        let (mut mean, mut square, mut product) = (0.0, 0.0, 0.0);
+        for (r, row) in e.iter().enumerate() {
+            let x = self.values[r] as f64;
+            for (s, &p) in row.iter().enumerate() {
+                mean += x * p;
+                square += x * x * p;
+                product += x * self.values[s] as f64 * p;
+            }
+        }
+        (product - mean * mean) / (square - mean * mean)
+    }
+}
+
+/// The FB100 attributes whose values are ordered, so that
+/// [`AttributeMixing::numeric_assortativity`] means something. The others
+/// are codes for categories.
+pub const ORDERED_ATTRIBUTES: [&str; 1] = ["year"];
+
+/// The FB100 attribute columns by name, in [`NodeData::ATTRIBUTES`] order.
+/// Missing values (0) are left out.
+pub fn fb100_attributes(node_data: &[NodeData]) -> Vec<(&'static str, HashMap<u64, i64>)> {
+    NodeData::ATTRIBUTES
+        .iter()
+        .enumerate()
+        .map(|(i, &name)| {
+            let column = node_data
+                .iter()
+                .map(|record| (record.node_id, record.attributes()[i] as i64))
+                .filter(|&(_, value)| value != 0)
+                .collect();
+            (name, column)
+        })
+        .collect()
+}
+
+/// The summary of one attribute's mixing, as written by the `homophily`
+/// subcommand. `numeric_assortativity` is NaN unless the attribute is one
+/// of the [`ORDERED_ATTRIBUTES`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HomophilyReport {
+    pub attribute: String,
+    pub nodes: usize,
+    pub edges: usize,
+    pub values: usize,
+    pub assortativity: f64,
+    pub numeric_assortativity: f64,
+    pub modularity: f64,
+}
+
+impl HomophilyReport {
+    pub fn new(attribute: &str, mixing: &AttributeMixing) -> Self {
+        Self {
+            attribute: attribute.to_owned(),
+            nodes: mixing.nodes,
+            edges: mixing.edges(),
+            values: mixing.values.len(),
+            assortativity: mixing.assortativity(),
+            numeric_assortativity: if ORDERED_ATTRIBUTES.contains(&attribute) {
+                mixing.numeric_assortativity()
+            } else {
+                f64::NAN
+            },
+            modularity: mixing.modularity(),
+        }
+    }
+}
+
+impl Row for HomophilyReport {
+    const COLUMNS: &'static [&'static str] = &[
+        "attribute",
+        "nodes",
+        "edges",
+        "values",
+        "assortativity",
+        "numeric_assortativity",
+        "modularity",
+    ];
+
+    fn fields(&self) -> Vec<Field> {
+        vec![
+            self.attribute.as_str().into(),
+            self.nodes.into(),
+            self.edges.into(),
+            self.values.into(),
+            self.assortativity.into(),
+            self.numeric_assortativity.into(),
+            self.modularity.into(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimpleNetwork;
+
+    #[test]
+    fn two_cliques_are_assortative() -> crate::Result<()> {
+        // triangles {0,1,2} (value 1) and {3,4,5} (value 2) joined by 2-3;
+        // node 6 has no value
+        let edges = [(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (2, 3), (5, 6)];
+        let network = SimpleNetwork::from_edges((0..7u64).collect(), edges)?;
+        let attribute: HashMap<u64, i64> = (0..6).map(|node| (node, 1 + node as i64 / 3)).collect();
+        let mixing = AttributeMixing::new(&network, &attribute);
+
+        assert_eq!((mixing.nodes, mixing.edges(), mixing.values.clone()), (6, 7, vec![1, 2]));
+        assert_eq!(mixing.counts, vec![vec![6, 1], vec![1, 6]]);
+        assert!(mixing.marginals().iter().all(|a| (a - 0.5).abs() < 1e-12));
+        // e_rr = 6/14 each, so Q = 12/14 - 1/2 and r = Q / (1 - 1/2)
+        assert!((mixing.modularity() - (6.0 / 7.0 - 0.5)).abs() < 1e-12);
+        assert!((mixing.assortativity() - 5.0 / 7.0).abs() < 1e-12);
+        // with two values the numeric coefficient is the same
+        assert!((mixing.numeric_assortativity() - 5.0 / 7.0).abs() < 1e-12);
+        // but it is only reported for ordered attributes
+        assert!((HomophilyReport::new("year", &mixing).numeric_assortativity - 5.0 / 7.0).abs() < 1e-12);
+        assert!(HomophilyReport::new("dorm", &mixing).numeric_assortativity.is_nan());
+        Ok(())
+    }
+}
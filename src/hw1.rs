@@ -8,6 +8,7 @@
 //! indicating that an edge exists between those two nodes.
 
 use serde::Deserialize;
+use crate::error::{Error, Result};
 use crate::node::Node;
 
 /// A record of a node
@@ -23,9 +24,20 @@ pub struct NodeData {
     year: i32,
 }
 
+impl NodeData {
+    /// Names of the attribute columns, in file order (excluding the id).
+    pub const ATTRIBUTES: [&'static str; 5] = ["status", "gender", "major", "dorm", "year"];
+
+    /// The attribute values, in the same order as [`NodeData::ATTRIBUTES`].
+    /// A value of 0 means the attribute is missing.
+    pub fn attributes(&self) -> [i32; 5] {
+        [self.status, self.gender, self.major, self.dorm, self.year]
+    }
+}
+
 /// Parse a file-like object into a vector of nodedata.
 ///
-pub fn parse_nodes<R: std::io::Read>(input: R) -> Result<Vec<NodeData>, csv::Error> {
+pub fn parse_nodes<R: std::io::Read>(input: R) -> Result<Vec<NodeData>> {
     let mut rdr = csv::ReaderBuilder::new()
         .has_headers(false)
         .delimiter(b'\t')
@@ -35,12 +47,13 @@ pub fn parse_nodes<R: std::io::Read>(input: R) -> Result<Vec<NodeData>, csv::Err
     rdr.deserialize().skip(1)
         // `Result` implements fromiterator, so when we collect this it will give us the first
         // error if there are any errors, or else will give us the vector of [`NodeData`]s.
+        .map(|record| record.map_err(Error::from))
         .collect()
 }
 
 /// Parse a file-like object into a vector of nodes - nodes are u64s here
 ///
-pub fn parse_basic_nodes<R: std::io::Read>(input: R) -> Result<Vec<u64>, csv::Error> {
+pub fn parse_basic_nodes<R: std::io::Read>(input: R) -> Result<Vec<u64>> {
     let mut rdr = csv::ReaderBuilder::new()
         .has_headers(false)
         .delimiter(b'\t')
@@ -50,6 +63,7 @@ pub fn parse_basic_nodes<R: std::io::Read>(input: R) -> Result<Vec<u64>, csv::Er
     rdr.deserialize()
         // `Result` implements fromiterator, so when we collect this it will give us the first
         // error if there are any errors, or else will give us the vector of [`NodeData`]s.
+        .map(|record| record.map_err(Error::from))
         .collect()
 }
 
@@ -64,7 +78,7 @@ pub struct Edge {
 
 /// Parse a file-like object into a vector of edges.
 ///
-pub fn parse_edges<R: std::io::Read>(input: R) -> Result<Vec<Edge>, csv::Error> {
+pub fn parse_edges<R: std::io::Read>(input: R) -> Result<Vec<Edge>> {
     let mut rdr = csv::ReaderBuilder::new()
         .has_headers(false)
         .delimiter(b'\t')
@@ -74,6 +88,7 @@ pub fn parse_edges<R: std::io::Read>(input: R) -> Result<Vec<Edge>, csv::Error>
     rdr.deserialize()
         // `Result` implements fromiterator, so when we collect this it will give us the first
         // error if there are any errors, or else will give us the vector of [`Edge`]s.
+        .map(|record| record.map_err(Error::from))
         .collect()
 }
 
@@ -86,7 +101,7 @@ pub struct Adjacency<N: Node = u64> {
 	pub edges: Vec<N>,
 }
 
-pub fn parse_adjacency_list<R: std::io::Read>(input: R) -> Result<Vec<Adjacency<u64>>, csv::Error> {
+pub fn parse_adjacency_list<R: std::io::Read>(input: R) -> Result<Vec<Adjacency<u64>>> {
     let mut rdr = csv::ReaderBuilder::new()
         .has_headers(false)
         .flexible(true)
@@ -124,7 +139,7 @@ impl Node for AttrNode {
 	}
 }
 
-pub fn parse_attr_nodes<R: std::io::Read>(input: R) -> Result<Vec<AttrNode>, csv::Error> {
+pub fn parse_attr_nodes<R: std::io::Read>(input: R) -> Result<Vec<AttrNode>> {
     let mut rdr = csv::ReaderBuilder::new()
         .has_headers(false)
         .delimiter(b',')
@@ -134,6 +149,7 @@ pub fn parse_attr_nodes<R: std::io::Read>(input: R) -> Result<Vec<AttrNode>, csv
     rdr.deserialize()
         // `Result` implements fromiterator, so when we collect this it will give us the first
         // error if there are any errors, or else will give us the vector of [`NodeData`]s.
+        .map(|record| record.map_err(Error::from))
         .collect()
 }
 
@@ -144,7 +160,7 @@ mod tests {
     use super::*;
 
     #[test]
-    fn parsing_nodes_works() -> Result<(), csv::Error> {
+    fn parsing_nodes_works() -> Result<()> {
         const DATA: &[u8] = b"id	status	gender	major	dorm	year
 1	2	3	4	5	6";
 
@@ -165,7 +181,7 @@ mod tests {
     }
 
     #[test]
-    fn multiline_node_parser() -> Result<(), csv::Error> {
+    fn multiline_node_parser() -> Result<()> {
         const DATA: &[u8] = b"id	status	gender	major	dorm	year
 1	2	3	4	5	6
 2	2	3	8	5	6
@@ -206,7 +222,7 @@ mod tests {
     }
 
     #[test]
-    fn parsing_edges_works() -> Result<(), csv::Error> {
+    fn parsing_edges_works() -> Result<()> {
         const DATA: &[u8] = b"12	1
 13	1
 2	3";
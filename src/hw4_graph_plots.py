@@ -7,6 +7,7 @@ import random as rnd
 rnd.seed()
 import copy
 import csv
+import json
 
 #blatantly stole the draw function from the in-class lab because I'm lazy
 def drawGz(G,z):
@@ -45,6 +46,7 @@ z1 = {}
 z2 = {}
 with open('output/hw4_onemove.csv', mode ='r')as file:
     csvFile = csv.reader(file)
+    next(csvFile) # header
     for line in csvFile:
         if line[0] == "Node":
             z1[line[2]] = int(line[3])
@@ -59,6 +61,7 @@ z3 = {}
 z4 = {}
 with open('output/hw4_onephase.csv', mode ='r')as file:
     csvFile = csv.reader(file)
+    next(csvFile) # header
     for line in csvFile:
         z3[line[1]] = int(line[2])
         z4[line[1]] = int(line[3])
@@ -66,21 +69,16 @@ with open('output/hw4_onephase.csv', mode ='r')as file:
 drawGz(G,z3)
 drawGz(G,z4)
 
-z5 = {}
-with open('output/hw4_samplegraph.csv', mode ='r')as file:
-    csvFile = csv.reader(file)
-    for line in csvFile:
-        z5[line[1]] = int(line[2])
+with open('output/hw4_sample_ps4.json', mode ='r')as file:
+    G_sample = nx.node_link_graph(json.load(file))
+z5 = nx.get_node_attributes(G_sample, 'group')
 
-drawGz(G,z5)
+drawGz(G_sample,z5)
 
-G2 = nx.read_edgelist("data/zkc.txt")
+with open('output/hw4_partition_zkc.json', mode ='r')as file:
+    G2 = nx.node_link_graph(json.load(file))
 
 n2  = G2.order()
-z6 = {}
-with open('output/hw4_partition_graph.csv', mode ='r')as file:
-    csvFile = csv.reader(file)
-    for line in csvFile:
-        z6[line[2]] = int(line[3])
+z6 = nx.get_node_attributes(G2, 'group')
 
 drawGz(G2,z6)
@@ -0,0 +1,115 @@
+//! Interning of string node labels.
+//!
+//! [`Node`](crate::node::Node) types have to be `Copy` and carry a `u64` id, so
+//! graphs whose nodes are names (e.g. the Medici families) are loaded by mapping
+//! each distinct label to a dense id `0..n`. The interner keeps the mapping so
+//! that results keyed by id can be reported back by label.
+
+use std::collections::HashMap;
+
+use crate::hw1::Adjacency;
+
+/// A two-way mapping between string labels and dense `u64` ids.
+///
+/// Ids are handed out in order of first appearance, starting at 0.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Interner {
+    ids: HashMap<String, u64>,
+    labels: Vec<String>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the id for `label`, assigning the next free id if it is new.
+    pub fn intern(&mut self, label: &str) -> u64 {
+        if let Some(&id) = self.ids.get(label) {
+            return id;
+        }
+        let id = self.labels.len() as u64;
+        self.ids.insert(label.to_owned(), id);
+        self.labels.push(label.to_owned());
+        id
+    }
+
+    /// The id of `label`, if it has been interned.
+    pub fn get(&self, label: &str) -> Option<u64> {
+        self.ids.get(label).copied()
+    }
+
+    /// The label of `id`, if it has been assigned.
+    pub fn label(&self, id: u64) -> Option<&str> {
+        self.labels.get(id as usize).map(String::as_str)
+    }
+
+    /// All labels, indexed by id.
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+}
+
+/// Renumbers an adjacency list by its `name` column.
+///
+/// Every row's name is interned, and its `node_id`, `id_again` and `edges` are
+/// rewritten to the interned ids. Edges to ids without a row are dropped.
+pub fn intern_adjacency_list(adj_list: Vec<Adjacency<u64>>, interner: &mut Interner) -> Vec<Adjacency<u64>> {
+    let renumber: HashMap<u64, u64> = adj_list
+        .iter()
+        .map(|adj| (adj.node_id, interner.intern(&adj.name)))
+        .collect();
+
+    adj_list
+        .into_iter()
+        .map(|adj| {
+            let id = renumber[&adj.node_id];
+            let edges: Vec<u64> = adj.edges.iter().filter_map(|e| renumber.get(e).copied()).collect();
+            Adjacency {
+                node_id: id,
+                id_again: id,
+                degree: edges.len() as u64,
+                edges,
+                ..adj
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hw1::parse_adjacency_list;
+
+    #[test]
+    fn labels_and_ids_map_both_ways() {
+        let mut interner = Interner::new();
+        assert_eq!([interner.intern("Medici"), interner.intern("Strozzi"), interner.intern("Medici")], [0, 1, 0]);
+        assert_eq!((interner.len(), interner.labels()), (2, &["Medici".to_owned(), "Strozzi".to_owned()][..]));
+        assert_eq!((interner.get("Strozzi"), interner.label(1)), (Some(1), Some("Strozzi")));
+        assert_eq!((interner.get("Pazzi"), interner.label(2)), (None, None));
+    }
+
+    #[test]
+    fn adjacency_lists_are_renumbered_by_name() -> crate::Result<()> {
+        // `id name id degree neighbor ...`; 30 has no row of its own
+        let adjs = parse_adjacency_list("10 Medici 10 2 20 30\n20 Strozzi 20 1 10\n".as_bytes())?;
+        let mut interner = Interner::new();
+        interner.intern("Pazzi");
+        let adjs = intern_adjacency_list(adjs, &mut interner);
+
+        assert_eq!(interner.labels(), &["Pazzi".to_owned(), "Medici".to_owned(), "Strozzi".to_owned()][..]);
+        let rows: Vec<(u64, u64, u64, Vec<u64>)> =
+            adjs.into_iter().map(|adj| (adj.node_id, adj.id_again, adj.degree, adj.edges)).collect();
+        assert_eq!(rows, vec![(1, 1, 1, vec![2]), (2, 2, 1, vec![1])]);
+        Ok(())
+    }
+}
@@ -1,11 +1,36 @@
 #![doc = include_str!("../README.md")]
 
+pub mod error;
 pub mod output;
 pub mod hw1;
+pub mod graph;
 pub mod network;
+pub mod analysis;
+pub mod view;
 pub mod node;
 pub mod statistic;
 pub mod partition;
+pub mod formats;
+pub mod stream;
+pub mod csr;
+pub mod bfs;
+pub mod distance;
+pub mod closeness;
+pub mod clustering;
+pub mod assortativity;
+pub mod degree;
+pub mod powerlaw;
+pub mod spectral;
+pub mod homophily;
+pub mod sampling;
+pub mod ensemble;
+pub mod snapshot;
+pub mod interner;
+pub mod experiment;
+pub mod checkpoint;
 
+pub use error::{Error, Result};
+pub use analysis::Analysis;
+pub use graph::Graph;
 pub use network::SimpleNetwork;
 
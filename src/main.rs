@@ -1,800 +1,1969 @@
-use std::fs::{read_dir, File};
+use std::collections::{HashMap, HashSet};
+use std::fs::{create_dir_all, File};
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
-use std::path::{Path,PathBuf};
+
 use anyhow::anyhow;
-use std::collections::{HashSet,HashMap};
-use rand::Rng;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::json;
 
+use hw5352::{
+	assortativity::{JointDegreeEntry, KnnClass},
+	checkpoint::Checkpoint,
+	clustering::DegreeClass,
+	csr::CsrGraph,
+	degree::{CcdfPoint, DegreeBin},
+	distance::hyper_anf,
+	ensemble::{Ensemble, NullModel, Significance},
+	error::Error,
+	experiment::ExperimentSpec,
+	formats::{parse_labeled_edges, parse_matrix_market, parse_snap_edges, EdgeList, LoadOptions},
+	homophily::{fb100_attributes, AttributeMixing, HomophilyReport},
+	hw1::{parse_adjacency_list, parse_attr_nodes, parse_basic_nodes, parse_nodes, AttrNode, Edge, NodeData},
+	interner::{intern_adjacency_list, Interner},
+	node::Node,
+	output::{Field, NodeLinkGraph, ResultWriter, Row, RunMetadata},
+	partition::{compute_mixing_matrix, fitDCSBM, log_likelyhood_DCSBM, makeAMove, runOnePhase},
+	powerlaw::{self, compare_exponential, compare_lognormal, goodness_of_fit},
+	sampling::Sampling,
+	snapshot::Snapshot,
+	spectral::Convergence,
+	statistic::{dp_scores, infer_attrs, jaccard_scores, remove_attrs, remove_edges, roc, sp_scores},
+	stream::{open_input, stream_network},
+	view::EdgeMask,
+	Analysis, SimpleNetwork,
+};
 
-use hw5352::{hw1::parse_edges, hw1::parse_nodes, hw1::AttrNode, hw1::parse_basic_nodes, hw1::parse_attr_nodes, hw1::parse_adjacency_list, output::to_csv, SimpleNetwork, statistic::remove_attrs, statistic::infer_attrs, statistic::remove_edges, statistic::jaccard_scores, statistic::dp_scores, statistic::sp_scores, statistic::roc, partition::compute_mixing_matrix, partition::log_likelyhood_DCSBM, partition::makeAMove, partition::runOnePhase, partition::fitDCSBM, node::Node};
+/// Network analysis tools from the CSCI 5352 homeworks.
+///
+/// Every subcommand takes its input files explicitly and appends its results
+/// to CSV/JSON files in the output directory. Each invocation is logged to
+/// `runs.jsonl` there, and result rows carry its run id.
+#[derive(Parser, Debug)]
+#[command(author, version, about)]
+struct Cli {
+	/// Directory for output files; relative `--output` paths are resolved against it
+	#[arg(short, long, global = true, default_value = "src/output")]
+	output_dir: PathBuf,
 
-use clap::Parser;
+	/// Seed for every random choice of the run [default: drawn at random];
+	/// either way it is recorded in `runs.jsonl`
+	#[arg(long, global = true)]
+	seed: Option<u64>,
 
-#[derive(Parser, Debug)]
-#[clap(author, version, about, long_about = None)]
-struct Args {
-    #[arg(short, long)]
-    w_hw_num: u32,
-
-    #[arg(short, long, default_value_t=format!(""))]
-    name: String,
-
-    #[clap(long, short, action)]
-    folder: bool,
-
-    #[clap(long, short, action)]
-    degree: bool,
-
-    #[clap(long, short, action)]
-    length: bool,
-    
-    #[clap(long, short, action)]
-    attr_inf: bool,
-    
-    #[clap(long, short, action)]
-    edge_inf: bool,
-    
-    #[clap(long, short, action)]
-    cnull_model: bool,
-    
-    #[clap(long, short, action)]
-    partition: bool,
-}
-//TODO: add documentation
-//good luck reading it until then
-
-/// Call with: cargo run [--release] -- -w <number> -n <name> options]
-/// Can also use cargo run -- --help to view full auto-generated
-/// options list as they might not all be here
-/// Universal Options:
-///		[-n]  			file/folder name (no extension or "_attr")
-///		[-f]			specifies to run on every file within target folder, rather than on a single data set
-/// W0 Options:			watts-strogatz testing
-/// W1 Options:
-///		[-d]			compute degree measures
-///		[-l]			compute distance measures
-/// 	[-c]			config model rewiring, gather stats at each step
-/// 	[-p]			run dc-sbm partition tests
-/// 	[-a]			run dc-sbm community detection repeatedly
-/// W2 Options:
-/// 	[-c]			generate null model for C and mgd using config model
-/// 	[-a]			delete and infer node attributes
-/// 	[-e]			edge inference
-/// W3 Options:			(adjacency list; harmonic centrality)
+	/// Save progress here after every dataset/replicate, and resume from it if
+	/// it exists. It is removed once the command finishes
+	#[arg(long, global = true)]
+	checkpoint: Option<PathBuf>,
 
-fn main() -> anyhow::Result<()> {
-    let now = Instant::now();
-    let args = Args::parse();
-    
-    if args.w_hw_num == 0 {
-		let max_nodes = 100;
-		let node_list: Vec<u64> = (0..max_nodes).collect();
-		let mut network_base: SimpleNetwork<u64> = SimpleNetwork::from_node_vec(node_list.clone());
-		for i in &node_list {
-			network_base.add_edge(*i,(*i+1)%max_nodes );
-			network_base.add_edge(*i,(*i+2)%max_nodes );
+	/// Worker threads for the per-source searches [default: one per core]
+	#[arg(long, global = true)]
+	threads: Option<usize>,
+
+	#[command(subcommand)]
+	command: Command,
+}
+
+#[derive(Subcommand, Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum Command {
+	/// Mean degree and mean square degree (HW1)
+	Stats(StatsArgs),
+	/// Mean geodesic distance, diameter and largest component size (HW1)
+	Distances(DistancesArgs),
+	/// Exact diameter (iFUB) and HyperANF estimates of the distance distribution
+	FastDistances(FastDistancesArgs),
+	/// Sampled estimates of mgd (with confidence interval), harmonic and betweenness centrality
+	Estimate(EstimateArgs),
+	/// Connected components: their number, sizes and the giant-component fraction
+	Components(ComponentsArgs),
+	/// Transitivity, average local clustering and the clustering spectrum C(k)
+	Clustering(ClusteringArgs),
+	/// Degree assortativity, mean neighbour degree k_nn(k) and the joint degree distribution
+	Assortativity(AssortativityArgs),
+	/// Assortativity by each FB100 attribute, for every school in a folder
+	Homophily(HomophilyArgs),
+	/// Degree distribution (CCDF, histograms) and a power-law fit to its tail
+	Degrees(DegreesArgs),
+	/// Clustering coefficient and mgd under the configuration model (HW2)
+	NullModel(NullModelArgs),
+	/// Fit a degree-corrected stochastic block model (HW4)
+	SbmFit(SbmFitArgs),
+	/// Hide node attributes and infer them by local smoothing (HW3)
+	InferAttrs(InferAttrsArgs),
+	/// Hide edges and score link predictors by ROC/AUC (HW3)
+	LinkPredict(LinkPredictArgs),
+	/// Harmonic, eigenvector, Katz and PageRank centrality; harmonic optionally against a null model (HW2)
+	Centrality(CentralityArgs),
+	/// Closeness centrality per component and with the Wasserman-Faust correction, or the top k
+	Closeness(ClosenessArgs),
+	/// Watts-Strogatz rewiring sweep with betweenness centrality (HW2)
+	Generate(GenerateArgs),
+	/// Run every experiment in a TOML/JSON spec file
+	Run(RunArgs),
+}
+
+impl Command {
+	/// The input files, for the subcommands that read networks.
+	fn input_mut(&mut self) -> Option<&mut InputArgs> {
+		match self {
+			Command::Stats(args) => Some(&mut args.input),
+			Command::Distances(args) => Some(&mut args.input),
+			Command::FastDistances(args) => Some(&mut args.input),
+			Command::Estimate(args) => Some(&mut args.input),
+			Command::Components(args) => Some(&mut args.input),
+			Command::Clustering(args) => Some(&mut args.input),
+			Command::Assortativity(args) => Some(&mut args.input),
+			Command::Homophily(args) => Some(&mut args.input),
+			Command::Degrees(args) => Some(&mut args.input),
+			Command::NullModel(args) => Some(&mut args.input),
+			Command::SbmFit(args) => Some(&mut args.input),
+			Command::InferAttrs(args) => Some(&mut args.input),
+			Command::LinkPredict(args) => Some(&mut args.input),
+			Command::Centrality(args) => Some(&mut args.input),
+			Command::Closeness(args) => Some(&mut args.input),
+			Command::Generate(_) | Command::Run(_) => None,
+		}
+	}
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum NullModelKind {
+	/// Configuration model: double edge swaps, keeping every degree
+	Swap,
+	/// Uniform random graphs with the same number of edges
+	Gnm,
+	/// Chung-Lu random graphs, keeping the degrees in expectation
+	ChungLu,
+}
+
+impl NullModelKind {
+	/// The model; edge swap draws are 2m swaps apart, after `burn_in` times
+	/// that, as `--burn-in` documents
+	fn model(self, burn_in: u64) -> NullModel {
+		match self {
+			NullModelKind::Swap => NullModel::EdgeSwap { burn_in: 2 * burn_in, spacing: 2 },
+			NullModelKind::Gnm => NullModel::Gnm,
+			NullModelKind::ChungLu => NullModel::ChungLu,
+		}
+	}
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum Format {
+	/// Matrix Market if the file starts with a `%%MatrixMarket` banner, else an edge list
+	Auto,
+	/// Whitespace/comma separated edge list with numeric ids (FB100, SNAP)
+	Edges,
+	/// Matrix Market coordinate file
+	Mtx,
+	/// Edge list with string node labels
+	Labeled,
+	/// `id name id degree neighbor weight ...` adjacency list (Padgett/Medici)
+	Adjacency,
+	/// Binary snapshot written with `--snapshot`
+	Snapshot,
+}
+
+/// Input files and how to read them.
+#[derive(Args, Clone, Debug, Serialize)]
+struct InputArgs {
+	/// Network files, one network each (gzip/zstd compressed files are fine),
+	/// or folders of FB100 schools
+	#[arg(required = true)]
+	inputs: Vec<PathBuf>,
+
+	/// Node files, one per input: an FB100 `_attr.txt` file or a list of ids.
+	/// Nodes listed here are kept even if they have no edges
+	#[arg(long)]
+	nodes: Vec<PathBuf>,
+
+	#[arg(long, value_enum, default_value_t = Format::Auto)]
+	format: Format,
+
+	/// Cache parsed networks as binary snapshots (`<input>.snap`) and reuse them
+	/// until the input or its node file changes. Labeled and adjacency inputs
+	/// are never cached, as snapshots keep numeric ids only
+	#[arg(long)]
+	snapshot: bool,
+}
+
+impl InputArgs {
+	/// Whether `--snapshot` applies: only inputs with numeric ids are cached,
+	/// since reloading labeled ones would lose their labels.
+	fn caches(&self) -> bool {
+		self.snapshot && !matches!(self.format, Format::Labeled | Format::Adjacency)
+	}
 
+	/// Replaces each directory among the inputs with the FB100 schools in
+	/// it: every `<school>.txt` that has a `<school>_attr.txt` beside it,
+	/// which becomes its node file. Inputs must be all files or all folders.
+	fn expand_folders(&self) -> anyhow::Result<InputArgs> {
+		if self.inputs.iter().all(|path| !path.is_dir()) {
+			return Ok(self.clone());
+		}
+		if !self.nodes.is_empty() {
+			return Err(anyhow!("--nodes cannot be given with a folder input"));
+		}
+		let mut expanded = InputArgs { inputs: vec![], nodes: vec![], ..self.clone() };
+		for path in &self.inputs {
+			if !path.is_dir() {
+				return Err(anyhow!("{} is not a folder; give either files or folders", path.display()));
+			}
+			let mut schools: Vec<PathBuf> = std::fs::read_dir(path)?
+				.map(|entry| Ok(entry?.path()))
+				.collect::<std::io::Result<Vec<_>>>()?
+				.into_iter()
+				.filter(|file| file.extension().is_some_and(|e| e == "txt") && attr_file(file).exists())
+				.collect();
+			schools.sort();
+			for school in schools {
+				expanded.nodes.push(attr_file(&school));
+				expanded.inputs.push(school);
+			}
 		}
-		let p_vals = 0..101;
-		for p in p_vals {
-			//rewire edges to new connections
-			let mut network = network_base.clone();
-			for i in &node_list{
-				if rand::thread_rng().gen_range(1..101) <= p{
-					network.rewire_edge(*i,(*i+1)%max_nodes);
+		if expanded.inputs.is_empty() {
+			return Err(anyhow!("no FB100 schools (`<school>.txt` with `<school>_attr.txt`) found"));
+		}
+		Ok(expanded)
+	}
+}
+
+/// `<school>_attr.txt` for `<school>.txt`
+fn attr_file(school: &Path) -> PathBuf {
+	let stem = school.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+	school.with_file_name(format!("{}_attr.txt", stem))
+}
+
+#[derive(Args, Debug, Serialize)]
+struct StatsArgs {
+	#[command(flatten)]
+	input: InputArgs,
+
+	/// Result file
+	#[arg(long, default_value = "hw1_degree_data.csv")]
+	output: PathBuf,
+
+	/// Number of inputs processed at once
+	#[arg(long, default_value_t = 1)]
+	jobs: usize,
+}
+
+#[derive(Args, Debug, Serialize)]
+struct DistancesArgs {
+	#[command(flatten)]
+	input: InputArgs,
+
+	/// Result file. This takes a long time - about 5.5 hours for all of FB100
+	#[arg(long, default_value = "hw1_distance_data.csv")]
+	output: PathBuf,
+
+	/// Number of inputs processed at once; each also searches in parallel
+	#[arg(long, default_value_t = 1)]
+	jobs: usize,
+}
+
+#[derive(Args, Debug, Serialize)]
+struct FastDistancesArgs {
+	#[command(flatten)]
+	input: InputArgs,
+
+	/// HyperLogLog counters get 2^log2m registers; the relative error of each
+	/// shrinks like 1/sqrt(2^log2m) while memory grows like 2^log2m per node
+	#[arg(long, default_value_t = 7)]
+	log2m: u32,
+
+	/// Fraction of pairs for the effective diameter
+	#[arg(long, default_value_t = 0.9)]
+	quantile: f64,
+
+	/// Result file
+	#[arg(long, default_value = "hw1_fast_distance_data.csv")]
+	output: PathBuf,
+
+	/// File for the estimated distance distribution: the fraction of pairs
+	/// at each distance, one row per input and distance
+	#[arg(long)]
+	distribution: Option<PathBuf>,
+
+	/// Number of inputs processed at once
+	#[arg(long, default_value_t = 1)]
+	jobs: usize,
+}
+
+#[derive(Args, Debug, Serialize)]
+struct EstimateArgs {
+	#[command(flatten)]
+	input: InputArgs,
+
+	/// Number of BFS sources sampled for the mgd and harmonic centrality
+	#[arg(long, default_value_t = 100)]
+	sources: usize,
+
+	/// Maximum error of the betweenness estimates
+	#[arg(long, default_value_t = 0.05)]
+	epsilon: f64,
+
+	/// Probability that some betweenness estimate is off by more than epsilon
+	#[arg(long, default_value_t = 0.1)]
+	delta: f64,
+
+	/// Result file
+	#[arg(long, default_value = "hw1_sampled_distance_data.csv")]
+	output: PathBuf,
+
+	/// Also estimate per-node centralities, written to this file
+	#[arg(long)]
+	centrality_output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Serialize)]
+struct ComponentsArgs {
+	#[command(flatten)]
+	input: InputArgs,
+
+	/// Result file, one row per input
+	#[arg(long, default_value = "hw1_components.csv")]
+	output: PathBuf,
+
+	/// File for the component sizes, one row per component, largest first
+	#[arg(long, default_value = "hw1_component_sizes.csv")]
+	sizes: PathBuf,
+
+	/// Also compute mgd and diameter of every component with at least two
+	/// nodes, written to this file
+	#[arg(long)]
+	distances: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Serialize)]
+struct ClusteringArgs {
+	#[command(flatten)]
+	input: InputArgs,
+
+	/// Result file, one row per input
+	#[arg(long, default_value = "hw2_clustering.csv")]
+	output: PathBuf,
+
+	/// File for the clustering spectrum: mean local clustering per degree
+	#[arg(long, default_value = "hw2_clustering_spectrum.csv")]
+	spectrum: PathBuf,
+
+	/// Also write every node's degree, triangle count and local clustering
+	/// to this file
+	#[arg(long)]
+	local: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Serialize)]
+struct AssortativityArgs {
+	#[command(flatten)]
+	input: InputArgs,
+
+	/// Result file, one row per input
+	#[arg(long, default_value = "hw1_assortativity.csv")]
+	output: PathBuf,
+
+	/// File for k_nn(k), the mean neighbour degree of the nodes of each degree
+	#[arg(long, default_value = "hw1_knn.csv")]
+	knn: PathBuf,
+
+	/// Also write the nonzero entries of the joint degree distribution to
+	/// this file
+	#[arg(long)]
+	joint: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Serialize)]
+struct DegreesArgs {
+	#[command(flatten)]
+	input: InputArgs,
+
+	/// Result file for the power-law fits, one row per input
+	#[arg(long, default_value = "hw1_powerlaw.csv")]
+	output: PathBuf,
+
+	/// File for the degree CCDF, P(K >= k)
+	#[arg(long, default_value = "hw1_ccdf.csv")]
+	ccdf: PathBuf,
+
+	/// File for the degree histogram with bins of `--bin-width`
+	#[arg(long, default_value = "hw1_degree_histogram.csv")]
+	histogram: PathBuf,
+
+	#[arg(long, default_value_t = 1)]
+	bin_width: usize,
+
+	/// File for the degree histogram with logarithmic bins
+	#[arg(long, default_value = "hw1_degree_log_histogram.csv")]
+	log_histogram: PathBuf,
+
+	/// Ratio between the edges of consecutive logarithmic bins
+	#[arg(long, default_value_t = 2.0)]
+	log_base: f64,
+
+	/// Synthetic data sets for the goodness-of-fit p-value; 0 skips it
+	#[arg(long, default_value_t = 1000)]
+	bootstrap: usize,
+}
+
+#[derive(Args, Debug, Serialize)]
+struct HomophilyArgs {
+	/// Inputs with `--nodes` attribute files, or folders of FB100 schools
+	/// (each `<school>.txt` with its `<school>_attr.txt`)
+	#[command(flatten)]
+	input: InputArgs,
+
+	/// Attributes to report [default: all in the attribute files]
+	#[arg(long, value_delimiter = ',', value_parser = clap::builder::PossibleValuesParser::new(NodeData::ATTRIBUTES))]
+	attributes: Vec<String>,
+
+	/// Result file, one row per school and attribute
+	#[arg(long, default_value = "hw4_homophily.csv")]
+	output: PathBuf,
+
+	/// Also write the normalized mixing matrices e_rs (nonzero entries) to
+	/// this file
+	#[arg(long)]
+	mixing: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Serialize)]
+struct NullModelArgs {
+	#[command(flatten)]
+	input: InputArgs,
+
+	/// Number of samples drawn from the configuration model
+	#[arg(long, default_value_t = 1000)]
+	reps: usize,
+
+	/// Burn-in, in units of 2m double edge swaps (one per edge end)
+	#[arg(long, default_value_t = 10)]
+	burn_in: u64,
+
+	/// Instead of sampling, measure along a single chain of `burn-in * 2m` swaps
+	/// at log-spaced points, to see how fast the statistics converge
+	#[arg(long)]
+	trace: bool,
+
+	/// Result file [default: hw2_p4_data.csv, or hw2_berkeley_data.csv with --trace]
+	#[arg(long)]
+	output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Serialize)]
+struct SbmFitArgs {
+	#[command(flatten)]
+	input: InputArgs,
+
+	/// Number of groups
+	#[arg(short = 'c', long, default_value_t = 2)]
+	groups: usize,
+
+	/// Maximum number of phases per fit
+	#[arg(short = 'T', long, default_value_t = 30)]
+	phases: usize,
+
+	/// Number of random restarts; the best fit is kept
+	#[arg(long, default_value_t = 5)]
+	reps: usize,
+
+	/// Also run the HW4 P4 checks: a single move, a single phase, and one fit,
+	/// all with c = 3 and at most 30 phases whatever `-c` and `-T` say
+	#[arg(long)]
+	diagnostics: bool,
+}
+
+#[derive(Args, Debug, Serialize)]
+struct InferAttrsArgs {
+	#[command(flatten)]
+	input: InputArgs,
+
+	/// Node attribute files (`id,attr`), one per input
+	#[arg(long, required = true)]
+	attrs: Vec<PathBuf>,
+
+	/// Number of observed fractions to try, evenly spaced in [0, 1)
+	#[arg(long, default_value_t = 50)]
+	steps: usize,
+
+	/// Replicates per observed fraction
+	#[arg(long, default_value_t = 50)]
+	reps: usize,
+
+	/// Result file
+	#[arg(long, default_value = "hw3_p1.csv")]
+	output: PathBuf,
+}
+
+#[derive(Args, Debug, Serialize)]
+struct LinkPredictArgs {
+	#[command(flatten)]
+	input: InputArgs,
+
+	/// Node attribute files (`id,attr`), one per input
+	#[arg(long, required = true)]
+	attrs: Vec<PathBuf>,
+
+	/// Number of observed fractions to try, evenly spaced in [0, 1)
+	#[arg(long, default_value_t = 20)]
+	steps: usize,
+
+	/// Replicates per observed fraction
+	#[arg(long, default_value_t = 50)]
+	reps: usize,
+
+	/// Step whose first replicate has its full ROC curves written out
+	#[arg(long, default_value_t = 16)]
+	roc_step: usize,
+
+	/// Result file
+	#[arg(long, default_value = "hw3_p2.csv")]
+	output: PathBuf,
+}
+
+#[derive(Args, Debug, Serialize)]
+struct CentralityArgs {
+	#[command(flatten)]
+	input: InputArgs,
+
+	/// Null-model samples to compare harmonic centrality against (0 to skip)
+	#[arg(long, default_value_t = 0)]
+	null_reps: usize,
+
+	#[arg(long, value_enum, default_value_t = NullModelKind::Swap)]
+	null_model: NullModelKind,
+
+	/// Burn-in for the edge-swap null model, in units of 2m double edge swaps
+	/// (one per edge end); draws are 2m swaps apart
+	#[arg(long, default_value_t = 10)]
+	burn_in: u64,
+
+	/// Result file
+	#[arg(long, default_value = "hw2_medici_data.csv")]
+	output: PathBuf,
+
+	/// File for the null-model draws, one row per node and draw
+	#[arg(long, default_value = "hw2_medici_dist.csv")]
+	null_output: PathBuf,
+
+	/// File for each node's z-score, p-value and null quantiles
+	#[arg(long, default_value = "hw2_medici_significance.csv")]
+	significance: PathBuf,
+
+	/// Also compute eigenvector, Katz and PageRank centrality, as extra
+	/// columns; a measure that does not converge is written as NaN
+	#[arg(long)]
+	spectral: bool,
+
+	/// Katz attenuation factor, below 1/lambda_max [default: half of
+	/// 1/lambda_max, or 0.5 on a graph without edges]
+	#[arg(long)]
+	katz_alpha: Option<f64>,
+
+	/// PageRank damping factor
+	#[arg(long, default_value_t = 0.85)]
+	damping: f64,
+
+	/// Nodes (by label) that PageRank restarts at, uniformly [default: all]
+	#[arg(long, value_delimiter = ',')]
+	personalize: Vec<String>,
+
+	/// Convergence tolerance for eigenvector, Katz and PageRank
+	#[arg(long, default_value_t = 1e-10)]
+	tolerance: f64,
+
+	/// Iteration cap for eigenvector, Katz and PageRank
+	#[arg(long, default_value_t = 1000)]
+	max_iterations: usize,
+}
+
+#[derive(Args, Debug, Serialize)]
+struct ClosenessArgs {
+	#[command(flatten)]
+	input: InputArgs,
+
+	/// Result file, one row per node (or per rank with `--top`)
+	#[arg(long, default_value = "hw2_closeness.csv")]
+	output: PathBuf,
+
+	/// Only find the k nodes of highest Wasserman-Faust closeness, pruning
+	/// the searches that cannot make it
+	#[arg(long)]
+	top: Option<usize>,
+}
+
+#[derive(Args, Debug, Serialize)]
+struct GenerateArgs {
+	/// Number of nodes in the ring lattice (each linked to 2 neighbors per side)
+	#[arg(long, default_value_t = 100)]
+	nodes: u64,
+
+	/// Rewiring probability is swept over 0, 1/steps, ..., 1
+	#[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..))]
+	steps: u32,
+
+	/// Result file, one row per step and node
+	#[arg(long, default_value = "hw2_ws_data.csv")]
+	output: PathBuf,
+}
+
+#[derive(Args, Debug, Serialize)]
+struct RunArgs {
+	/// Experiment spec; read as JSON if it ends in `.json`, else as TOML
+	spec: PathBuf,
+
+	/// Print the expanded runs without running them
+	#[arg(long)]
+	dry_run: bool,
+}
+
+/// Parses the command line of one run from an experiment spec.
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+struct SpecRun {
+	#[command(subcommand)]
+	command: Command,
+}
+
+/// Things every subcommand needs.
+struct Context {
+	out_dir: PathBuf,
+	run: RunMetadata,
+	start: Instant,
+	/// Drives the null-model and rewiring chains, so that they can be resumed
+	rng: ChaCha8Rng,
+	checkpoint: Option<Checkpoint>,
+}
+
+impl Context {
+	/// Sets up the RNG and checkpoint for a run, resuming from `checkpoint`
+	/// if it exists (in which case the original run id and seed are kept).
+	/// An unseeded run draws its seed here, so that it too can be repeated.
+	fn new(out_dir: PathBuf, mut run: RunMetadata, checkpoint: Option<PathBuf>, key: &str) -> anyhow::Result<Self> {
+		let seed = *run.seed.get_or_insert_with(rand::random);
+		let mut rng = ChaCha8Rng::seed_from_u64(seed);
+		let checkpoint = match checkpoint {
+			Some(path) => match Checkpoint::resume(&path, key)? {
+				Some(resumed) => {
+					println!("Resuming from {}", path.display());
+					run.run_id = resumed.run_id.clone();
+					run.seed = resumed.seed.or(run.seed);
+					run = run.with_parameter("resumed", true);
+					rng = resumed.rng.restore();
+					Some(resumed)
+				}
+				None => {
+					let mut checkpoint = Checkpoint::new(&path, key, &run.run_id, &rng);
+					checkpoint.seed = run.seed;
+					Some(checkpoint)
 				}
-				if rand::thread_rng().gen_range(1..101) <= p{
-					network.rewire_edge(*i,(*i+2)%max_nodes);
+			},
+			None => None,
+		};
+		Ok(Self {
+			out_dir,
+			run,
+			start: Instant::now(),
+			rng,
+			checkpoint,
+		})
+	}
+
+	/// A result writer for `file`, relative to the output directory.
+	fn writer(&mut self, file: &Path, columns: &[&str]) -> anyhow::Result<ResultWriter> {
+		let path = self.out_dir.join(file);
+		self.track(&path)?;
+		let dir = path.parent().unwrap_or(&self.out_dir);
+		let name = path
+			.file_name()
+			.and_then(|n| n.to_str())
+			.ok_or(anyhow!("invalid output file {}", file.display()))?;
+		Ok(ResultWriter::open(dir, name, columns, &self.run)?)
+	}
+
+	fn track(&mut self, path: &Path) -> anyhow::Result<()> {
+		match &mut self.checkpoint {
+			Some(checkpoint) => Ok(checkpoint.track_output(path)?),
+			None => Ok(()),
+		}
+	}
+
+	/// Whether `unit` was finished by an earlier, interrupted attempt.
+	fn is_done(&self, unit: &str) -> bool {
+		self.checkpoint.as_ref().is_some_and(|c| c.is_done(unit))
+	}
+
+	fn complete(&mut self, unit: &str) -> anyhow::Result<()> {
+		match &mut self.checkpoint {
+			Some(checkpoint) => Ok(checkpoint.complete(unit, &self.rng)?),
+			None => Ok(()),
+		}
+	}
+
+	fn save_chain(&mut self, unit: &str, step: u64, network: &SimpleNetwork<u64>) -> anyhow::Result<()> {
+		match &mut self.checkpoint {
+			Some(checkpoint) => Ok(checkpoint.save_chain(unit, step, network)?),
+			None => Ok(()),
+		}
+	}
+
+	fn load_chain(&self, unit: &str) -> anyhow::Result<Option<(u64, SimpleNetwork<u64>)>> {
+		match &self.checkpoint {
+			Some(checkpoint) => Ok(checkpoint.load_chain(unit)?),
+			None => Ok(None),
+		}
+	}
+
+	/// Removes the checkpoint once everything is done.
+	fn finish(&mut self) -> anyhow::Result<()> {
+		match self.checkpoint.take() {
+			Some(checkpoint) => Ok(checkpoint.finish()?),
+			None => Ok(()),
+		}
+	}
+
+	fn report(&self, split: Instant) {
+		println!("Split: {:.2?}, Total: {:.2?}", split.elapsed(), self.start.elapsed());
+	}
+}
+
+/// A network read from one input, with whatever came along with it.
+struct Loaded {
+	name: String,
+	network: SimpleNetwork<u64>,
+	/// Present when the nodes were identified by label
+	interner: Option<Interner>,
+	/// The FB100 attribute columns by name, when the node file has them.
+	/// Missing values are left out
+	attributes: Vec<(String, HashMap<u64, i64>)>,
+}
+
+impl Loaded {
+	fn label(&self, node: u64) -> String {
+		self.interner
+			.as_ref()
+			.and_then(|i| i.label(node))
+			.map(str::to_owned)
+			.unwrap_or_else(|| node.to_string())
+	}
+
+	/// The node with `label`, the inverse of [`Loaded::label`]
+	fn node(&self, label: &str) -> Option<u64> {
+		let node = match &self.interner {
+			Some(interner) => interner.get(label)?,
+			None => label.parse().ok()?,
+		};
+		self.network.has_node(node).then_some(node)
+	}
+
+	/// The network in node-link form, with its labels and attribute columns
+	fn node_link(&self) -> serde_json::Result<NodeLinkGraph<u64>> {
+		let mut graph = NodeLinkGraph::from_network(&self.network);
+		if let Some(interner) = &self.interner {
+			graph = graph.with_labels(interner);
+		}
+		for (attribute, values) in &self.attributes {
+			graph = graph.with_node_attribute(attribute, values)?;
+		}
+		Ok(graph)
+	}
+}
+
+/// The dataset name for a file: its name without extension (or compression suffix).
+fn dataset_name(path: &Path) -> String {
+	let mut path = path.to_path_buf();
+	if matches!(path.extension().and_then(|e| e.to_str()), Some("gz" | "zst" | "snap")) {
+		path.set_extension("");
+	}
+	path.file_stem()
+		.and_then(|s| s.to_str())
+		.unwrap_or("network")
+		.to_owned()
+}
+
+/// Reads a node file: an FB100 `_attr` file (with its header line) or a bare id list.
+fn read_nodes(path: &Path) -> anyhow::Result<(Vec<u64>, Vec<NodeData>)> {
+	println!("Opening {}", path.display());
+	let mut reader = open_input(path)?;
+	let is_attr = reader.fill_buf()?.first().is_some_and(|b| !b.is_ascii_digit());
+	if is_attr {
+		let data = parse_nodes(reader)?;
+		Ok((data.iter().map(|d| d.node_id).collect(), data))
+	} else {
+		Ok((parse_basic_nodes(reader)?, vec![]))
+	}
+}
+
+/// Reads an edge list in any of the edge-list formats.
+fn read_edge_list(path: &Path, format: Format, interner: &mut Option<Interner>) -> anyhow::Result<EdgeList> {
+	println!("Opening {}", path.display());
+	let mut reader = open_input(path)?;
+	let is_mtx = reader.fill_buf()?.starts_with(b"%%MatrixMarket");
+	let edge_list = match format {
+		Format::Mtx => parse_matrix_market(reader, LoadOptions::default()),
+		Format::Auto if is_mtx => parse_matrix_market(reader, LoadOptions::default()),
+		Format::Labeled => {
+			parse_labeled_edges(reader, LoadOptions::default(), interner.get_or_insert_with(Interner::new))
+		}
+		Format::Auto | Format::Edges => parse_snap_edges(reader, LoadOptions::default()),
+		Format::Adjacency | Format::Snapshot => return Err(anyhow!("{:?} input is not an edge list", format)),
+	};
+	Ok(edge_list?)
+}
+
+/// Whether `cache` exists and was written after every one of `sources` was
+/// last modified.
+fn is_fresh(cache: &Path, sources: &[&Path]) -> bool {
+	let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+	match modified(cache) {
+		Some(cached) => sources.iter().all(|&source| modified(source).is_some_and(|m| m <= cached)),
+		None => false,
+	}
+}
+
+/// The checkpoint unit for the `i`th input.
+fn dataset_unit(input: &InputArgs, i: usize) -> String {
+	format!("{}/{}", i, dataset_name(&input.inputs[i]))
+}
+
+/// Reads the `i`th input as a `SimpleNetwork`.
+fn load(input: &InputArgs, i: usize) -> anyhow::Result<Loaded> {
+	if !input.nodes.is_empty() && input.nodes.len() != input.inputs.len() {
+		return Err(anyhow!("give either no --nodes files or one per input"));
+	}
+	let path = &input.inputs[i];
+	let name = dataset_name(path);
+
+	let snapshot_path = PathBuf::from(format!("{}.snap", path.display()));
+	let sources: Vec<&Path> = std::iter::once(path.as_path()).chain(input.nodes.get(i).map(PathBuf::as_path)).collect();
+	if input.format == Format::Snapshot || (input.caches() && is_fresh(&snapshot_path, &sources)) {
+		let file = if input.format == Format::Snapshot { path } else { &snapshot_path };
+		println!("Loading snapshot {}", file.display());
+		let snapshot = Snapshot::load(file)?;
+		let attributes = snapshot
+			.columns
+			.iter()
+			.map(|column| {
+				let values = (0..snapshot.graph.num_nodes())
+					.map(|i| (snapshot.graph.node(i), column.values[i]))
+					.filter(|&(_, value)| value != 0)
+					.collect();
+				(column.name.clone(), values)
+			})
+			.collect();
+		let network = snapshot.to_network()?;
+		return Ok(Loaded { name, network, interner: None, attributes });
+	}
+
+	let (node_ids, node_data) = match input.nodes.get(i) {
+		Some(nodes) => read_nodes(nodes)?,
+		None => (vec![], vec![]),
+	};
+	let mut interner = None;
+	let network = match input.format {
+		Format::Adjacency => {
+			println!("Opening {}", path.display());
+			let mut adjs = parse_adjacency_list(open_input(path)?)?;
+			// neighbors are followed by edge weights - keep the neighbors only
+			for adj in &mut adjs {
+				if adj.degree == 0 {
+					adj.edges = vec![];
+				} else {
+					adj.edges = adj.edges.iter().step_by(2).copied().collect();
 				}
 			}
-			//betweenness centrality
-			let btn_c = network.betweenness_centrality();
-			let name = "WStest";
-			let btn_vec = btn_c.into_values().collect::<Vec<u64>>();
-			let data_file = File::options()
-                    .append(true)
-                    .create(true)
-                    .open("src/output/hw2_ws_data.csv")?;
-                    
-            to_csv(&name, &[(p as f64)/100.0], &btn_vec, data_file)?;
-            println!(".");
-			
-		}
-		
-    } else if args.w_hw_num == 1 {
-        let mut node_data = vec![];
-        let mut edge_data = vec![];
-        let mut filename_nodes = format!("src/data/{}_attr.txt", args.name);
-		let mut filename_edges = format!("src/data/{}.txt", args.name);
-		// filepath management, just getting all filenames from the folder
-		// should be in same position in vecs, unless something fails weirdly
-		// does assume that any *_attr.txt file has a corresponding *.txt
-        if args.folder {
-            let foldername = &format!("src/data/{}", args.name);
-            let paths = read_dir(foldername).unwrap();
-
-            for entry in paths {
-				let path_buf = entry?.path();
-				let path_str = path_buf.to_str().ok_or(anyhow!("non-utf8 path"))?;
-                if path_str.contains("_attr.txt") {
-					let edge_str = path_str.replace("_attr.txt",".txt");
-                    node_data.push(path_str.to_owned());
-                    edge_data.push(edge_str);
-                }
-            }
-        } else{
-			//dummy values so the for loop happens
-			node_data.push(filename_nodes.clone());
-			edge_data.push(filename_edges.clone());
-		}
-        for i in 0..node_data.len() {
-			let split = Instant::now();
-			if args.folder {
-				filename_nodes = node_data[i].clone();
-				filename_edges = edge_data[i].clone();
+			// nodes are renumbered by name so results can be reported by label
+			let mut labels = Interner::new();
+			let adjs = intern_adjacency_list(adjs, &mut labels);
+			interner = Some(labels);
+			SimpleNetwork::from_adj_list(adjs)?
+		}
+		Format::Auto | Format::Edges if !node_ids.is_empty() => {
+			// FB100: edges are streamed straight into the network, on top of
+			// the node file's nodes so that isolated students are kept
+			println!("Opening {}", path.display());
+			stream_network(open_input(path)?, node_ids, LoadOptions::default())?
+		}
+		format => {
+			let mut edge_list = read_edge_list(path, format, &mut interner)?;
+			edge_list.nodes.extend(node_ids);
+			edge_list.nodes.sort();
+			edge_list.nodes.dedup();
+			edge_list.into_network()?
+		}
+	};
+
+	if input.caches() {
+		Snapshot::from_network(&network).with_node_data(&node_data).save(&snapshot_path)?;
+	}
+	let attributes = fb100_attributes(&node_data)
+		.into_iter()
+		.filter(|(_, values)| !values.is_empty())
+		.map(|(attribute, values)| (attribute.to_owned(), values))
+		.collect();
+	Ok(Loaded { name, network, interner, attributes })
+}
+
+/// Reads the `i`th input as an edge list, together with its `id,attr` file.
+fn load_attr_nodes(input: &InputArgs, attrs: &[PathBuf], i: usize) -> anyhow::Result<(String, EdgeList, Vec<AttrNode>)> {
+	if attrs.len() != input.inputs.len() {
+		return Err(anyhow!("give one --attrs file per input"));
+	}
+	let path = &input.inputs[i];
+	let edges = read_edge_list(path, input.format, &mut None)?;
+	println!("Opening {}", attrs[i].display());
+	let nodes = parse_attr_nodes(open_input(&attrs[i])?)?;
+	Ok((dataset_name(path), edges, nodes))
+}
+
+/// Builds the network of attributed nodes. Edges with an endpoint that has
+/// no attribute record are skipped.
+fn attr_network(nodes: &[AttrNode], edges: &[Edge]) -> anyhow::Result<SimpleNetwork<AttrNode>> {
+	let by_id: HashMap<u64, AttrNode> = nodes.iter().map(|&node| (node.node_id, node)).collect();
+	let mut network = SimpleNetwork::from_node_vec(nodes.to_vec());
+	for edge in edges {
+		if let (Some(&from), Some(&to)) = (by_id.get(&edge.from), by_id.get(&edge.to)) {
+			network.add_edge(from, to)?;
+		}
+	}
+	Ok(network)
+}
+
+/// Loads and processes the unfinished inputs, `jobs` at a time, and writes
+/// each one's row (in input order) before marking it done.
+fn for_each_input<F>(
+	ctx: &mut Context,
+	input: &InputArgs,
+	jobs: usize,
+	out: &mut ResultWriter,
+	compute: F,
+) -> anyhow::Result<()>
+where
+	F: Fn(Loaded) -> anyhow::Result<Vec<Field>> + Sync,
+{
+	let pending: Vec<usize> = (0..input.inputs.len())
+		.filter(|&i| !ctx.is_done(&dataset_unit(input, i)))
+		.collect();
+	for chunk in pending.chunks(jobs.max(1)) {
+		let split = Instant::now();
+		let rows: Vec<anyhow::Result<Vec<Field>>> = chunk.par_iter().map(|&i| compute(load(input, i)?)).collect();
+		for (&i, row) in chunk.iter().zip(rows) {
+			out.write_row(&row?)?;
+			ctx.complete(&dataset_unit(input, i))?;
+		}
+		ctx.report(split);
+	}
+	Ok(())
+}
+
+fn stats(ctx: &mut Context, args: &StatsArgs) -> anyhow::Result<()> {
+	let mut out = ctx.writer(&args.output, &["name", "mean_degree", "mean_square_degree", "edges", "nodes"])?;
+	for_each_input(ctx, &args.input, args.jobs, &mut out, |Loaded { name, network, .. }| {
+		let mean_degree = (2 * network.num_edges()) as f64 / (network.num_nodes() as f64);
+		let mean_square_degree = network.mean_square_degree();
+		// also recording number of nodes and edges for sanity checks
+		Ok(vec![
+			name.into(),
+			mean_degree.into(),
+			mean_square_degree.into(),
+			network.num_edges().into(),
+			network.num_nodes().into(),
+		])
+	})
+}
+
+fn distances(ctx: &mut Context, args: &DistancesArgs) -> anyhow::Result<()> {
+	let mut out = ctx.writer(&args.output, &["name", "mgd", "diameter", "lcc_size"])?;
+	for_each_input(ctx, &args.input, args.jobs, &mut out, |Loaded { name, network, .. }| {
+		let (mgd, diameter, size) = network.mgd_diameter();
+		Ok(vec![name.into(), mgd.into(), diameter.into(), size.into()])
+	})
+}
+
+/// Like `distances`, on the largest component, but with an exact diameter
+/// from a few searches and the mgd estimated by HyperANF.
+fn fast_distances(ctx: &mut Context, args: &FastDistancesArgs) -> anyhow::Result<()> {
+	let mut out = ctx.writer(
+		&args.output,
+		&["name", "mgd_estimate", "effective_diameter", "diameter", "lcc_size", "searches"],
+	)?;
+	let distributions = std::sync::Mutex::new(match &args.distribution {
+		Some(file) => Some(ctx.writer(file, &["name", "distance", "fraction"])?),
+		None => None,
+	});
+	for_each_input(ctx, &args.input, args.jobs, &mut out, |Loaded { name, network, .. }| {
+		let components = network.connected_components();
+		let lcc = network.induced_subgraph(components.members(0).iter().copied());
+		let diameter = lcc.diameter();
+		let anf = hyper_anf(&CsrGraph::from_network(&lcc), args.log2m, 0);
+		if let Some(file) = distributions.lock().map_err(|_| anyhow!("poisoned lock"))?.as_mut() {
+			for (i, fraction) in anf.distance_distribution().into_iter().enumerate() {
+				file.write_row(&[name.as_str().into(), (i + 1).into(), fraction.into()])?;
+			}
+		}
+		Ok(vec![
+			name.into(),
+			anf.mean_distance().into(),
+			anf.effective_diameter(args.quantile).into(),
+			(diameter.diameter as u64).into(),
+			lcc.num_nodes().into(),
+			diameter.searches.into(),
+		])
+	})
+}
+
+fn estimate(ctx: &mut Context, args: &EstimateArgs) -> anyhow::Result<()> {
+	let mut out = ctx.writer(
+		&args.output,
+		&["name", "mgd", "std_error", "lower", "upper", "sources", "lcc_size"],
+	)?;
+	let mut centrality_out = match &args.centrality_output {
+		Some(file) => Some(ctx.writer(file, &["name", "node", "harmonic", "betweenness"])?),
+		None => None,
+	};
+	for i in 0..args.input.inputs.len() {
+		let unit = dataset_unit(&args.input, i);
+		if ctx.is_done(&unit) {
+			continue;
+		}
+		let split = Instant::now();
+		let loaded = load(&args.input, i)?;
+		let network = &loaded.network;
+
+		let mgd = network.mgd_sampled(args.sources, &mut ctx.rng);
+		let lcc_size = network.connected_components().sizes.first().copied().unwrap_or(0);
+		out.write_row(&[
+			loaded.name.as_str().into(),
+			mgd.value.into(),
+			mgd.std_error.into(),
+			mgd.lower.into(),
+			mgd.upper.into(),
+			mgd.samples.into(),
+			lcc_size.into(),
+		])?;
+
+		if let Some(centrality_out) = &mut centrality_out {
+			let harmonic = network.harmonic_centrality_sampled(args.sources, &mut ctx.rng);
+			let betweenness = network.betweenness_sampled(args.epsilon, args.delta, &mut ctx.rng);
+			let mut nodes: Vec<u64> = network.nodes().collect();
+			nodes.sort();
+			for node in nodes {
+				centrality_out.write_row(&[
+					loaded.name.as_str().into(),
+					loaded.label(node).into(),
+					harmonic[&node].into(),
+					betweenness[&node].into(),
+				])?;
 			}
-			println!("Opening {}",filename_nodes.clone());
-            println!("Opening {}",filename_edges.clone());
-            let nodes_file = File::open(filename_nodes.clone())?;
-            let edges_file = File::open(filename_edges.clone())?;
-            
-            let path = Path::new(&filename_edges);
-            let name = path.file_stem().unwrap().to_str().unwrap();
-
-            let nodes = parse_nodes(nodes_file)?;
-            let edges = parse_edges(edges_file)?;
-            //dbg!(nodes.clone());
-            //dbg!(edges.clone());
-
-            let node_list: Vec<u64> = nodes.into_iter().map(|n| n.node_id).collect();
-            let mut network: SimpleNetwork<u64> = SimpleNetwork::from_node_vec(node_list);
-            for edge in edges {
-                network.add_edge(edge.from, edge.to)?;
-            }
-            //dbg!(network.clone());
-            //The fast stuff - mean degree and mean squared degree
-            if args.degree {
-                let degree_file = File::options()
-                    .append(true)
-                    .create(true)
-                    .open("src/output/hw1_degree_data.csv")?;
-                //dbg!("output one open");
-
-                let mean_degree = (network.total_edges as f64) / (network.nodes.len() as f64);
-                let mean_square_degree = network.mean_square_degree();
-
-                //output to hw1_degree_data.csv
-                //also recording number of nodes and edges for sanity checks
-                to_csv(&name, &[mean_degree, mean_square_degree], &[network.total_edges/2,network.nodes.len() as u64],degree_file)?;
-                println!(".");
-            }
-            //mgd, diameter, and largest connected component
-			//This takes a long time to run - about 5.5 hours for the facebook100 dataset
-            if args.length {
-                let distance_file = File::options()
-                    .append(true)
-                    .create(true)
-                    .open("src/output/hw1_distance_data.csv")?;
-                //dbg!("output two open");
-
-                let (mgd,diameter,size) = network.mgd_diameter();
-
-                //output to hw1_distance_data.csv
-                to_csv(&name, &[mgd], &[diameter, size], distance_file)?;
-                println!(".");
-            }
-            
-            if args.cnull_model {
-				let steps = 10*network.total_edges;
-                let config_berkeley_1 = File::options()
-                    .append(true)
-                    .create(true)
-                    .open("src/output/hw2_berkeley_data.csv")?;
-                let (mgd_actual,_,_) = network.mgd_diameter();
-				let cluster_actual = network.cluster_coeff();
-				//output to hw2_p4_data.csv
-				//dbg!(network.clone());
-               	to_csv(&name, &[cluster_actual,mgd_actual], &[0], config_berkeley_1)?;
-               	let ratio = f64::powf(steps as f64, 1.0 / 25.0);
-               	let measure_steps = (1..101).map(|x| ratio.powi(x) as u64).collect::<Vec<u64>>();
-				for i in 0..steps {
-					//double edge swap on network
-					network.double_edge_swap()?;
-					
-					if measure_steps.contains(&i){
-						let config_berkeley = File::options()
-		                    .append(true)
-		                    .create(true)
-		                    .open("src/output/hw2_berkeley_data.csv")?;
-						
-						//measure C and mgd
-						let (mgd,_,_) = network.mgd_diameter();
-						let cluster = network.cluster_coeff();
-						//output to hw2_p4_data.csv
-						//dbg!(network.clone());
-	               		to_csv(&name, &[cluster,mgd], &[i], config_berkeley)?;
-	               		dbg!(".");
-               		}
-               	}
-                println!(".");
+		}
+		ctx.complete(&unit)?;
+		ctx.report(split);
+	}
+	Ok(())
+}
+
+fn components(ctx: &mut Context, args: &ComponentsArgs) -> anyhow::Result<()> {
+	let mut out = ctx.writer(
+		&args.output,
+		&["name", "nodes", "edges", "components", "giant_size", "giant_fraction"],
+	)?;
+	let mut sizes_out = ctx.writer(&args.sizes, &["name", "component", "size"])?;
+	let mut distance_out = match &args.distances {
+		Some(file) => Some(ctx.writer(file, &["name", "component", "size", "mgd", "diameter"])?),
+		None => None,
+	};
+	for i in 0..args.input.inputs.len() {
+		let unit = dataset_unit(&args.input, i);
+		if ctx.is_done(&unit) {
+			continue;
+		}
+		let split = Instant::now();
+		let Loaded { name, network, .. } = load(&args.input, i)?;
+
+		let components = network.connected_components();
+		out.write_row(&[
+			name.as_str().into(),
+			network.num_nodes().into(),
+			network.num_edges().into(),
+			components.count().into(),
+			components.sizes.first().copied().unwrap_or(0).into(),
+			components.giant_fraction().into(),
+		])?;
+		for (label, &size) in components.sizes.iter().enumerate() {
+			sizes_out.write_row(&[name.as_str().into(), label.into(), size.into()])?;
+		}
+
+		if let Some(distance_out) = &mut distance_out {
+			let members = components.members_by_label().iter().take_while(|members| members.len() > 1);
+			for (label, members) in members.enumerate() {
+				let size = members.len();
+				let (mgd, diameter, _) = network.induced_subgraph(members.iter().copied()).mgd_diameter();
+				distance_out.write_row(&[
+					name.as_str().into(),
+					label.into(),
+					size.into(),
+					mgd.into(),
+					diameter.into(),
+				])?;
+			}
+		}
+		ctx.complete(&unit)?;
+		ctx.report(split);
+	}
+	Ok(())
+}
+
+fn clustering(ctx: &mut Context, args: &ClusteringArgs) -> anyhow::Result<()> {
+	let mut out = ctx.writer(
+		&args.output,
+		&["name", "triangles", "transitivity", "average_clustering"],
+	)?;
+	let mut spectrum_out = ctx.writer(&args.spectrum, &DegreeClass::header())?;
+	let mut local_out = match &args.local {
+		Some(file) => Some(ctx.writer(file, &["name", "node", "degree", "triangles", "clustering"])?),
+		None => None,
+	};
+	for i in 0..args.input.inputs.len() {
+		let unit = dataset_unit(&args.input, i);
+		if ctx.is_done(&unit) {
+			continue;
+		}
+		let split = Instant::now();
+		let loaded = load(&args.input, i)?;
+		let name = loaded.name.as_str();
+
+		let clustering = loaded.network.clustering();
+		out.write_row(&[
+			name.into(),
+			clustering.total_triangles().into(),
+			clustering.transitivity().into(),
+			clustering.average().into(),
+		])?;
+		spectrum_out.write_rows(name, clustering.spectrum())?;
+		if let Some(local_out) = &mut local_out {
+			let local = clustering.local();
+			for (i, &node) in clustering.nodes.iter().enumerate() {
+				local_out.write_row(&[
+					name.into(),
+					loaded.label(node).into(),
+					clustering.degrees[i].into(),
+					clustering.triangles[i].into(),
+					local[i].into(),
+				])?;
 			}
-			
-			if args.partition {
-				//part (a) of HW4 P4 - c=3, random partition, f_2 = 1 but all else 0
-				let mut rng = rand::thread_rng();
-				let mut partition = HashMap::new();
-				let c = 3;
-				for node in &network.nodes {
-					if node.id() == 2 {
-						partition.insert(*node, (rng.gen_range(0..c), true));
-					} else {
-						partition.insert(*node, (rng.gen_range(0..c), false));
+		}
+		ctx.complete(&unit)?;
+		ctx.report(split);
+	}
+	Ok(())
+}
+
+/// Degree correlations. `mean_neighbor_degree` is the mean of `k_nn` over
+/// nodes with at least one neighbour, to set against `mean_degree` for the
+/// friendship paradox; isolated nodes have no neighbours to average.
+fn assortativity(ctx: &mut Context, args: &AssortativityArgs) -> anyhow::Result<()> {
+	let mut out = ctx.writer(
+		&args.output,
+		&["name", "assortativity", "mean_degree", "mean_neighbor_degree"],
+	)?;
+	let mut knn_out = ctx.writer(&args.knn, &KnnClass::header())?;
+	let mut joint_out = match &args.joint {
+		Some(file) => Some(ctx.writer(file, &JointDegreeEntry::header())?),
+		None => None,
+	};
+	for i in 0..args.input.inputs.len() {
+		let unit = dataset_unit(&args.input, i);
+		if ctx.is_done(&unit) {
+			continue;
+		}
+		let split = Instant::now();
+		let Loaded { name, network, .. } = load(&args.input, i)?;
+
+		let joint = network.joint_degree_matrix();
+		let knn = network.average_neighbor_degree();
+		let connected: Vec<f64> = knn.iter().filter(|&(&node, _)| network.degree(node) > 0).map(|(_, &k)| k).collect();
+		out.write_row(&[
+			name.as_str().into(),
+			joint.assortativity().into(),
+			network.mean_degree_empirical().into(),
+			(connected.iter().sum::<f64>() / connected.len() as f64).into(),
+		])?;
+		knn_out.write_rows(&name, network.knn_spectrum())?;
+		if let Some(joint_out) = &mut joint_out {
+			joint_out.write_rows(&name, joint.entries())?;
+		}
+		ctx.complete(&unit)?;
+		ctx.report(split);
+	}
+	Ok(())
+}
+
+/// Degree distributions, and the Clauset-Shalizi-Newman power-law fit to
+/// each. The likelihood ratios are positive where the power law fits better
+/// than the alternative. Inputs with fewer than two distinct nonzero degrees
+/// get no fit (NaN).
+fn degrees(ctx: &mut Context, args: &DegreesArgs) -> anyhow::Result<()> {
+	// checked up front, so a bad value fails before any input is read
+	if args.bin_width == 0 {
+		return Err(anyhow!("--bin-width must be positive"));
+	}
+	if !(args.log_base > 1.0 && args.log_base.is_finite()) {
+		return Err(anyhow!("--log-base must be finite and greater than 1, got {}", args.log_base));
+	}
+	let mut out = ctx.writer(
+		&args.output,
+		&[
+			"name",
+			"nodes",
+			"max_degree",
+			"alpha",
+			"x_min",
+			"tail",
+			"ks",
+			"p",
+			"lognormal_ratio",
+			"lognormal_p",
+			"exponential_ratio",
+			"exponential_p",
+		],
+	)?;
+	let mut ccdf_out = ctx.writer(&args.ccdf, &CcdfPoint::header())?;
+	let mut histogram_out = ctx.writer(&args.histogram, &DegreeBin::header())?;
+	let mut log_histogram_out = ctx.writer(&args.log_histogram, &DegreeBin::header())?;
+	for i in 0..args.input.inputs.len() {
+		let unit = dataset_unit(&args.input, i);
+		if ctx.is_done(&unit) {
+			continue;
+		}
+		let split = Instant::now();
+		let Loaded { name, network, .. } = load(&args.input, i)?;
+
+		let distribution = network.degree_distribution();
+		let degrees = distribution.sequence();
+		let mut row: Vec<Field> = vec![name.as_str().into(), degrees.len().into(), distribution.max().into()];
+		match powerlaw::fit(degrees) {
+			Some(fit) => {
+				let p = match args.bootstrap {
+					0 => f64::NAN,
+					reps => goodness_of_fit(degrees, &fit, reps, &mut ctx.rng)?,
+				};
+				let lognormal = compare_lognormal(degrees, &fit);
+				let exponential = compare_exponential(degrees, &fit);
+				row.extend([
+					fit.alpha.into(),
+					fit.x_min.into(),
+					fit.tail.into(),
+					fit.ks.into(),
+					p.into(),
+					lognormal.ratio.into(),
+					lognormal.p.into(),
+					exponential.ratio.into(),
+					exponential.p.into(),
+				]);
+			}
+			None => row.extend((0..9).map(|_| Field::from(f64::NAN))),
+		}
+		out.write_row(&row)?;
+		ccdf_out.write_rows(&name, distribution.ccdf())?;
+		histogram_out.write_rows(&name, distribution.histogram(args.bin_width)?)?;
+		log_histogram_out.write_rows(&name, distribution.log_histogram(args.log_base)?)?;
+		ctx.complete(&unit)?;
+		ctx.report(split);
+	}
+	Ok(())
+}
+
+/// Attribute assortativity per school and attribute. Schools are run in
+/// folder order; a school without attribute data is an error.
+fn homophily(ctx: &mut Context, args: &HomophilyArgs) -> anyhow::Result<()> {
+	let input = &args.input;
+	let mut out = ctx.writer(&args.output, &HomophilyReport::header())?;
+	let mut mixing_out = match &args.mixing {
+		Some(file) => Some(ctx.writer(file, &["name", "attribute", "r", "s", "e_rs"])?),
+		None => None,
+	};
+	for i in 0..input.inputs.len() {
+		let unit = dataset_unit(input, i);
+		if ctx.is_done(&unit) {
+			continue;
+		}
+		let split = Instant::now();
+		let Loaded { name, network, attributes, .. } = load(input, i)?;
+		if attributes.is_empty() {
+			return Err(anyhow!("{} has no attribute data; give its _attr file with --nodes", name));
+		}
+		for (attribute, values) in &attributes {
+			if !args.attributes.is_empty() && !args.attributes.contains(attribute) {
+				continue;
+			}
+			let mixing = AttributeMixing::new(&network, values);
+			out.write_rows(&name, [HomophilyReport::new(attribute, &mixing)])?;
+			if let Some(mixing_out) = &mut mixing_out {
+				for (r, row) in mixing.normalized().iter().enumerate() {
+					for (s, &e) in row.iter().enumerate().filter(|(_, &e)| e > 0.0) {
+						mixing_out.write_row(&[
+							name.as_str().into(),
+							attribute.as_str().into(),
+							mixing.values[r].into(),
+							mixing.values[s].into(),
+							e.into(),
+						])?;
 					}
 				}
-				let mut mixing_matrix = compute_mixing_matrix(&network, c, &partition);
-				let mut grp_degrees = vec![];
-				for grp in &mixing_matrix {
-					grp_degrees.push(grp.iter().sum());
+			}
+		}
+		ctx.complete(&unit)?;
+		ctx.report(split);
+	}
+	Ok(())
+}
+
+/// Samples the configuration model by double edge swaps.
+///
+/// With `--checkpoint`, the chain is saved after burn-in and after every
+/// sample (or every measurement, with `--trace`), and resumed from there.
+fn null_model(ctx: &mut Context, args: &NullModelArgs) -> anyhow::Result<()> {
+	for i in 0..args.input.inputs.len() {
+		let unit = dataset_unit(&args.input, i);
+		if ctx.is_done(&unit) {
+			continue;
+		}
+		let split = Instant::now();
+		let Loaded { name, mut network, .. } = load(&args.input, i)?;
+		let steps = args.burn_in * 2 * network.num_edges();
+		let resumed = ctx.load_chain(&unit)?;
+
+		if args.trace {
+			let output = args.output.clone().unwrap_or_else(|| "hw2_berkeley_data.csv".into());
+			let mut out = ctx.writer(&output, &["name", "cluster", "mgd", "swaps"])?;
+			let start = match resumed {
+				Some((step, chain)) => {
+					network = chain;
+					step
 				}
-				
-				let init_likelyhood = log_likelyhood_DCSBM(&mixing_matrix,&grp_degrees);
-				let (new_likelyhood, (moved_node, dst)) = makeAMove(&network, &partition, c);
-				for (node, (grp,frozen)) in partition {
-					let config_onemove = File::options()
-		                    .append(true)
-		                    .create(true)
-		                    .open("src/output/hw4_onemove.csv")?;
-						
-					//dbg!(network.clone());
-	              	to_csv("Node", &[init_likelyhood], &[node.id(),grp as u64], config_onemove)?;
-	               	dbg!(".");
-	            }
-	            let config_onemove = File::options()
-		                    .append(true)
-		                    .create(true)
-		                    .open("src/output/hw4_onemove.csv")?;
-						
-					//dbg!(network.clone());
-	              	to_csv("Move", &[new_likelyhood], &[moved_node.id(),dst as u64], config_onemove)?;
-	               	dbg!(".");
-	               	
-	            //part (b) - test one phase
-				let mut partition2 = HashMap::new();
-				for node in &network.nodes {
-					if node.id() == 2 {
-						partition2.insert(*node, (rng.gen_range(0..c), false));
-					} else {
-						partition2.insert(*node, (rng.gen_range(0..c), false));
-					}
+				None => {
+					let (mgd_actual, _, _) = network.mgd_diameter();
+					let cluster_actual = network.cluster_coeff();
+					out.write_row(&[name.as_str().into(), cluster_actual.into(), mgd_actual.into(), 0u64.into()])?;
+					0
+				}
+			};
+
+			let ratio = f64::powf(steps as f64, 1.0 / 25.0);
+			let measure_steps = (1..101).map(|x| ratio.powi(x) as u64).collect::<Vec<u64>>();
+			for step in start..steps {
+				network.double_edge_swap_with_rng(&mut ctx.rng)?;
+				if measure_steps.contains(&step) {
+					let (mgd, _, _) = network.mgd_diameter();
+					let cluster = network.cluster_coeff();
+					out.write_row(&[name.as_str().into(), cluster.into(), mgd.into(), step.into()])?;
+					ctx.save_chain(&unit, step + 1, &network)?;
+					ctx.complete(&format!("{}/swaps/{}", unit, step))?;
 				}
-				
-				let (new_partition2, new_likelyhood2, halt, likelyhoods) = runOnePhase(&network, partition2.clone(), c);
-				for (node, (grp,frozen)) in partition2 {
-					let config_onephase = File::options()
-		                    .append(true)
-		                    .create(true)
-		                    .open("src/output/hw4_onephase.csv")?;
-					if let Some((new_grp,_)) = new_partition2.get(&node) {
-						//dbg!(network.clone());
-	              		to_csv("Node", &[], &[node.id(),grp as u64,*new_grp as u64], config_onephase)?;
-	               		dbg!(".");
-	               	}
-	            }
-				let config_onephase_ls = File::options()
-		                    .append(true)
-		                    .create(true)
-		                    .open("src/output/hw4_onephase_ls.csv")?;
-				//dbg!(network.clone());
-	            to_csv(&name, &likelyhoods, &[], config_onephase_ls)?;
-	            dbg!(".");
-	            
-	            //part (c) - fitDCSBM once with T=30
-	            
-	            let (partition3, likelyhood3, likelyhoods3) = fitDCSBM(network.clone(), c, 30);
-				let mixing_matrix = compute_mixing_matrix(&network, c, &partition3);
-				for (node, (grp,frozen)) in partition3 {
-					let config_sample = File::options()
-		                    .append(true)
-		                    .create(true)
-		                    .open("src/output/hw4_samplegraph.csv")?;
-					//dbg!(network.clone());
-	              	to_csv(&name, &[], &[node.id(),grp as u64], config_sample)?;
-	            	dbg!(".");
-	            }
-	            for row in mixing_matrix {
-					let config_sample = File::options()
-		                    .append(true)
-		                    .create(true)
-		                    .open("src/output/hw4_samplematrix.csv")?;
-					//dbg!(network.clone());
-					let mut row_u64 = vec![];
-					for item in row {
-						row_u64.push(item as u64);
-					}
-	              	to_csv(&name, &[], &row_u64, config_sample)?;
-	            	dbg!(".");
-	            }
-				let config_sample = File::options()
-		                    .append(true)
-		                    .create(true)
-		                    .open("src/output/hw4_samplels.csv")?;
-				//dbg!(network.clone());
-	            to_csv(&name, &likelyhoods3, &[], config_sample)?;
-	            dbg!(".");
 			}
-			
-			if args.attr_inf {
-				let reps = 5;
-				let c = 2;
-				let T = 30;
-				let mut best_partition = HashMap::new();
-				let mut best_likelyhood = 0.0;
-				let mut best_likelyhoods = vec![];
-				let mut attempt = 0;
-				//dbg!(network.clone());
-				for i in 0..reps {
-					let (partition, likelyhood, likelyhoods) = fitDCSBM(network.clone(), c, T);
-					if best_likelyhood == 0.0 || likelyhood > best_likelyhood {
-						(best_partition, best_likelyhood, best_likelyhoods) = (partition, likelyhood, likelyhoods);
-						attempt = i+1;
-					}
+		} else {
+			let output = args.output.clone().unwrap_or_else(|| "hw2_p4_data.csv".into());
+			let mut out = ctx.writer(&output, &["name", "cluster", "mgd"])?;
+			let first_rep = match resumed {
+				Some((rep, chain)) => {
+					network = chain;
+					rep
 				}
-				let mixing_matrix = compute_mixing_matrix(&network, c, &best_partition);
-				for (node, (grp,frozen)) in best_partition {
-					let config_graph = File::options()
-		                    .append(true)
-		                    .create(true)
-		                    .open("src/output/hw4_partition_graph.csv")?;
-					//dbg!(network.clone());
-	              	to_csv(&name, &[], &[attempt,node.id(),grp as u64], config_graph)?;
-	            	dbg!(".");
-	            }
-	            for row in mixing_matrix {
-					let config_matrix = File::options()
-		                    .append(true)
-		                    .create(true)
-		                    .open("src/output/hw4_matrix.csv")?;
-					//dbg!(network.clone());
-					let mut row_u64 = vec![];
-					for item in row {
-						row_u64.push(item as u64);
+				None => {
+					// the observed values go first, then the samples
+					let (mgd_actual, _, _) = network.mgd_diameter();
+					let cluster_actual = network.cluster_coeff();
+					out.write_row(&[name.as_str().into(), cluster_actual.into(), mgd_actual.into()])?;
+					for _ in 0..steps {
+						network.double_edge_swap_with_rng(&mut ctx.rng)?;
 					}
-	              	to_csv(&name, &[], &row_u64, config_matrix)?;
-	            	dbg!(".");
-	            }
-				let config_ls = File::options()
-		                    .append(true)
-		                    .create(true)
-		                    .open("src/output/hw4_ls_list.csv")?;
-				//dbg!(network.clone());
-	            to_csv(&name, &best_likelyhoods, &[], config_ls)?;
-	            dbg!(".");
-				
+					ctx.save_chain(&unit, 0, &network)?;
+					ctx.complete(&format!("{}/burn-in", unit))?;
+					0
+				}
+			};
+
+			let swaps = 2 * network.num_edges();
+			for rep in first_rep..args.reps as u64 {
+				let crg_split = Instant::now();
+				for _ in 0..swaps {
+					network.double_edge_swap_with_rng(&mut ctx.rng)?;
+				}
+				let (mgd, _, _) = network.mgd_diameter();
+				let cluster = network.cluster_coeff();
+				out.write_row(&[name.as_str().into(), cluster.into(), mgd.into()])?;
+				ctx.save_chain(&unit, rep + 1, &network)?;
+				ctx.complete(&format!("{}/rep/{}", unit, rep))?;
+				println!("CRG in: {:.2?}, Total: {:.2?}", crg_split.elapsed(), ctx.start.elapsed());
 			}
-			
-            
-            let partial_time = split.elapsed();
-            let elapsed = now.elapsed();
-            println!("Split: {:.2?}, Total: {:.2?}", partial_time, elapsed);
-        }
-    } else if args.w_hw_num == 2 {
-		//Need to check if 1 or 2 files, then read in (basic data)
-        let mut node_data = vec![];
-        let mut edge_data = vec![];
-        let mut filename_nodes = format!("src/data/{}_nodes.txt", args.name);
-		let mut filename_edges = format!("src/data/{}.txt", args.name);
-		// filepath management, just getting all filenames from the folder
-		// should be in same position in vecs, unless something fails weirdly
-		// does assume that any *_attr.txt file has a corresponding *.txt
-        if args.folder {
-            let foldername = &format!("src/data/{}", args.name);
-            let paths = read_dir(foldername).unwrap();
-
-            for entry in paths {
-				let path_buf = entry?.path();
-				let path_str = path_buf.to_str().ok_or(anyhow!("non-utf8 path"))?;
-				
-				if let Some(extension) = path_buf.extension(){
-	                if extension == "txt" && !path_str.contains("_nodes.txt") {
-						let node_str = &path_str.replace(".txt","_nodes.txt");
-						let mut candidate = PathBuf::new();
-						candidate.set_file_name(&node_str);
-						if candidate.exists() {
-							node_data.push(node_str.to_owned());
-						} else {
-	 					   node_data.push(format!(""));
-						}
-	
-	                    edge_data.push(path_str.to_owned());
-	                }
-                }
-            }
-        } else{
-			//dummy values so the for loop happens
-			node_data.push(filename_nodes.clone());
-			edge_data.push(filename_edges.clone());
-		}
-        for i in 0..node_data.len() {
-			let split = Instant::now();
-			if args.folder {
-				filename_nodes = node_data[i].clone();
-				filename_edges = edge_data[i].clone();
+		}
+		ctx.complete(&unit)?;
+		ctx.report(split);
+	}
+	Ok(())
+}
+
+fn sbm_fit(ctx: &mut Context, args: &SbmFitArgs) -> anyhow::Result<()> {
+	let c = args.groups;
+	for i in 0..args.input.inputs.len() {
+		let unit = dataset_unit(&args.input, i);
+		if ctx.is_done(&unit) {
+			continue;
+		}
+		let split = Instant::now();
+		let loaded = load(&args.input, i)?;
+		let (name, network) = (&loaded.name, &loaded.network);
+
+		if args.diagnostics {
+			sbm_diagnostics(ctx, &loaded)?;
+		}
+
+		let mut best_partition = HashMap::new();
+		let mut best_likelyhood = 0.0;
+		let mut best_likelyhoods = vec![];
+		let mut attempt = 0;
+		for rep in 0..args.reps {
+			let (partition, likelyhood, likelyhoods) = fitDCSBM(network, c, args.phases, &mut ctx.rng);
+			if best_likelyhood == 0.0 || likelyhood > best_likelyhood {
+				(best_partition, best_likelyhood, best_likelyhoods) = (partition, likelyhood, likelyhoods);
+				attempt = rep + 1;
 			}
+		}
+		let mixing_matrix = compute_mixing_matrix(network, c, &best_partition);
+		loaded
+			.node_link()?
+			.with_partition("group", &best_partition)?
+			.with_graph_attribute("name", &name)?
+			.with_graph_attribute("c", &c)?
+			.with_graph_attribute("reps", &args.reps)?
+			.with_graph_attribute("attempt", &attempt)?
+			.with_graph_attribute("log_likelihood", &best_likelyhood)?
+			.with_graph_attribute("log_likelihoods", &best_likelyhoods)?
+			.with_graph_attribute("mixing_matrix", &mixing_matrix)?
+			.write(File::create(ctx.out_dir.join(format!("hw4_partition_{}.json", name)))?)?;
+		write_trace(ctx, "hw4_ls_list.csv", name, &best_likelyhoods)?;
+		ctx.complete(&unit)?;
+		ctx.report(split);
+	}
+	Ok(())
+}
 
-    		
-            
-            //doing hw3 node attribute inference
-			if args.attr_inf {
-				println!("Opening {}",filename_edges.clone());
-	            let edges_file = File::open(filename_edges.clone())?;
-	            
-	            let path = Path::new(&filename_edges);
-	            let name = path.file_stem().unwrap().to_str().unwrap();
-	            
-	            let edges = parse_edges(edges_file)?;
-	
-				println!("Opening {}",filename_nodes.clone());
-				let nodes_file = File::open(filename_nodes.clone())?;
-				let mut nodes = parse_attr_nodes(nodes_file)?;
-				nodes.sort_by(|a,b| a.node_id.cmp(&b.node_id));
-	            //dbg!(nodes.clone());
-	            //dbg!(edges.clone());
-	            
-	            let alpha_unit = 50.0;
-	            for alpha in 0..50 {
-					let alpha_split = Instant::now();
-					for rep in 0..50 {
-						let nodes_redacted = remove_attrs(nodes.clone(),(alpha as f64)/alpha_unit);
-						//dbg!(nodes_redacted.clone());
-						let mut network: SimpleNetwork<AttrNode> = SimpleNetwork::from_node_vec(nodes_redacted.clone());
-		            	for edge in &edges {
-							let from_node = nodes_redacted.iter().find(|&x| x.node_id == edge.from);
-							let to_node = nodes_redacted.iter().find(|&x| x.node_id == edge.to);
-							if from_node.is_some() && to_node.is_some() {
-								network.add_edge(*from_node.unwrap(), *to_node.unwrap())?;
-							}
-		            	}
-		            	//dbg!(network.clone());
-		            	
-		            	let num_observed = ((nodes.len() as f64)*(alpha as f64)/alpha_unit) as usize;
-		            	let mut baseline;
-		            	if num_observed > 0{
-							baseline = nodes_redacted.iter().map(|&x| x.attr).collect::<Vec<i32>>();
-						} else {
-							baseline = nodes.iter().map(|&x| x.attr).collect::<Vec<i32>>();
-						}
-						baseline.sort();
-						baseline.dedup();
-		            	
-		            	let mut inferred = infer_attrs(network,baseline);
-		            	let num_guessed = inferred.len() - ((nodes.len() as f64)*(alpha as f64)/alpha_unit) as usize;
-		            	inferred.sort_by(|a,b| a.node_id.cmp(&b.node_id));
-		            	let num_wrong = inferred.iter().zip(nodes.clone().iter()).filter(|(a,b)| a.attr != b.attr).count();
-		            	let acc = 1.0 - (num_wrong as f64)/(num_guessed as f64);
-		            	
-		            	//dbg!(inferred.clone());
-		            	let config_c_mgd = File::options()
-	                    .append(true)
-	                    .create(true)
-	                    .open("src/output/hw3_p1.csv")?;
-						
-						
-						//output
-						//dbg!(network.clone());
-	                	to_csv(&name, &[(alpha as f64)/alpha_unit, acc], &[], config_c_mgd)?;
-					}
-					let alpha_time = alpha_split.elapsed();
-            		let elapsed = now.elapsed();
-		           	println!("alpha={} done in: {:.2?}, Total: {:.2?}", alpha, alpha_time, elapsed);
-				}
-	            
-	            
+/// HW4 P4 (a)-(c): one move from a random partition with node 2 frozen, one
+/// phase from a random partition, and a single fit.
+fn sbm_diagnostics(ctx: &mut Context, loaded: &Loaded) -> anyhow::Result<()> {
+	let (name, network) = (loaded.name.as_str(), &loaded.network);
+	// the problem fixes c = 3 and T = 30
+	let (c, phases) = (3, 30);
+	// part (a) - random partition, f_2 = 1 but all else 0
+	let mut partition = HashMap::new();
+	for node in network.nodes() {
+		partition.insert(node, (ctx.rng.gen_range(0..c), node.id() == 2));
+	}
+	let mixing_matrix = compute_mixing_matrix(network, c, &partition);
+	let grp_degrees: Vec<usize> = mixing_matrix.iter().map(|grp| grp.iter().sum()).collect();
+	let init_likelyhood = log_likelyhood_DCSBM(&mixing_matrix, &grp_degrees);
+	let (new_likelyhood, (moved_node, dst)) = makeAMove(network, &partition, c);
+
+	let mut onemove_out = ctx.writer(Path::new("hw4_onemove.csv"), &["kind", "log_likelihood", "node", "group"])?;
+	for node in network.nodes() {
+		let (grp, _) = partition[&node];
+		onemove_out.write_row(&["Node".into(), init_likelyhood.into(), node.id().into(), grp.into()])?;
+	}
+	onemove_out.write_row(&["Move".into(), new_likelyhood.into(), moved_node.id().into(), dst.into()])?;
+
+	// part (b) - test one phase
+	let partition2: HashMap<u64, (usize, bool)> =
+		network.nodes().map(|node| (node, (ctx.rng.gen_range(0..c), false))).collect();
+	let (new_partition2, _, _, likelyhoods) = runOnePhase(network, partition2.clone(), c);
+	let mut onephase_out = ctx.writer(Path::new("hw4_onephase.csv"), &["kind", "node", "group", "new_group"])?;
+	for node in network.nodes() {
+		if let (Some((grp, _)), Some((new_grp, _))) = (partition2.get(&node), new_partition2.get(&node)) {
+			onephase_out.write_row(&["Node".into(), node.id().into(), (*grp).into(), (*new_grp).into()])?;
+		}
+	}
+	write_trace(ctx, "hw4_onephase_ls.csv", name, &likelyhoods)?;
+
+	// part (c) - fitDCSBM once
+	let (partition3, likelyhood3, likelyhoods3) = fitDCSBM(network, c, phases, &mut ctx.rng);
+	let mixing_matrix = compute_mixing_matrix(network, c, &partition3);
+	// partition, mixing matrix and likelihood trace together as node-link JSON
+	loaded
+		.node_link()?
+		.with_partition("group", &partition3)?
+		.with_graph_attribute("name", &name)?
+		.with_graph_attribute("c", &c)?
+		.with_graph_attribute("log_likelihood", &likelyhood3)?
+		.with_graph_attribute("log_likelihoods", &likelyhoods3)?
+		.with_graph_attribute("mixing_matrix", &mixing_matrix)?
+		.write(File::create(ctx.out_dir.join(format!("hw4_sample_{}.json", name)))?)?;
+	write_trace(ctx, "hw4_samplels.csv", name, &likelyhoods3)?;
+	Ok(())
+}
+
+/// Writes a log-likelihood trace to `file`, one row per step.
+fn write_trace(ctx: &mut Context, file: &str, name: &str, likelyhoods: &[f64]) -> anyhow::Result<()> {
+	let mut out = ctx.writer(Path::new(file), &["name", "step", "log_likelihood"])?;
+	for (step, likelyhood) in likelyhoods.iter().enumerate() {
+		out.write_row(&[name.into(), step.into(), (*likelyhood).into()])?;
+	}
+	Ok(())
+}
+
+fn infer_attributes(ctx: &mut Context, args: &InferAttrsArgs) -> anyhow::Result<()> {
+	let mut out = ctx.writer(&args.output, &["name", "alpha", "accuracy"])?;
+	let alpha_unit = args.steps as f64;
+	for i in 0..args.input.inputs.len() {
+		let unit = dataset_unit(&args.input, i);
+		if ctx.is_done(&unit) {
+			continue;
+		}
+		let (name, edge_list, mut nodes) = load_attr_nodes(&args.input, &args.attrs, i)?;
+		let edges = edge_list.edges;
+		nodes.sort_by_key(|a| a.node_id);
+
+		for alpha in 0..args.steps {
+			let alpha_unit_name = format!("{}/alpha/{}", unit, alpha);
+			if ctx.is_done(&alpha_unit_name) {
+				continue;
 			}
-			//hw3 edge inference
-			else if args.edge_inf {
-				println!("Opening {}",filename_edges.clone());
-	            let edges_file = File::open(filename_edges.clone())?;
-	            
-	            let path = Path::new(&filename_edges);
-	            let name = path.file_stem().unwrap().to_str().unwrap();
-	            
-	            let edges = parse_edges(edges_file)?;
-
-				println!("Opening {}",filename_nodes.clone());
-				let nodes_file = File::open(filename_nodes.clone())?;
-				let nodes = parse_attr_nodes(nodes_file)?;
-				
-				
-				let mut full_network: SimpleNetwork<AttrNode> = SimpleNetwork::from_node_vec(nodes.clone());
-		        for edge in &edges {
-					let from_node = nodes.iter().find(|&x| x.node_id == edge.from);
-					let to_node = nodes.iter().find(|&x| x.node_id == edge.to);
-					if from_node.is_some() && to_node.is_some() {
-						full_network.add_edge(*from_node.unwrap(), *to_node.unwrap())?;
-					}
-		   		}
-	            
-	            let edges_as_pairs = full_network.edgelist();
-	            
-	            //dbg!(nodes.clone());
-	            //dbg!(edges.clone());
-	            let alpha_unit = 20.0;
-	            for alpha in 0..20 {
-					let alpha_split = Instant::now();
-					for rep in 0..50 {
-						let edges_redacted = remove_edges(edges.clone(),(alpha as f64)/alpha_unit);
-						//dbg!(nodes_redacted.clone());
-						let mut network: SimpleNetwork<AttrNode> = SimpleNetwork::from_node_vec(nodes.clone());
-		            	for edge in &edges_redacted {
-							let from_node = nodes.iter().find(|&x| x.node_id == edge.from);
-							let to_node = nodes.iter().find(|&x| x.node_id == edge.to);
-							if from_node.is_some() && to_node.is_some() {
-								network.add_edge(*from_node.unwrap(), *to_node.unwrap())?;
-							}
-		            	}
-		            	//dbg!(network.clone());
-		            	
-		            	//let score_split = Instant::now();
-		            	let j_scores = jaccard_scores(network.clone());
-		            	let d_scores = dp_scores(network.clone());
-		            	let s_scores = sp_scores(network.clone());
-		            	//dbg!(score_split.elapsed());
-		            	//dbg!(j_scores.len());
-		            	//dbg!(j_scores.clone());
-		            	//dbg!(d_scores.clone());
-		            	//dbg!(s_scores.clone());
-		            	let j_roc = roc(j_scores,edges_as_pairs.clone());
-		            	let d_roc = roc(d_scores,edges_as_pairs.clone());
-		            	let s_roc = roc(s_scores,edges_as_pairs.clone());
-		            	//dbg!(score_split.elapsed());
-
-		            	
-		            	if alpha == 16 && rep == 0 {
-							let j_roc_file = File::options()
-	                    	.append(true)
-	                    	.create(true)
-	                    	.open("src/output/hw3_jroc.csv")?;
-						
-							//output
-							//dbg!(network.clone());
-							for item in &j_roc {
-	                			to_csv(&name, &[item.0,item.1], &[], j_roc_file.try_clone()?)?;
-	                		}
-	                		let d_roc_file = File::options()
-	                    	.append(true)
-	                    	.create(true)
-	                    	.open("src/output/hw3_droc.csv")?;
-						
-							//output
-							//dbg!(network.clone());
-							for item in &d_roc {
-	                			to_csv(&name, &[item.0,item.1], &[], d_roc_file.try_clone()?)?;
-	                		}
-	                		let s_roc_file = File::options()
-	                    	.append(true)
-	                    	.create(true)
-	                    	.open("src/output/hw3_sroc.csv")?;
-						
-							//output
-							//dbg!(network.clone());
-							for item in &s_roc {
-	                			to_csv(&name, &[item.0,item.1], &[], s_roc_file.try_clone()?)?;
-	                		}
-						}
-						
-						let mut j_auc = 0.0;
-						let mut d_auc = 0.0;
-						let mut s_auc = 0.0;
-						//dbg!(j_roc.clone());
-						//dbg!(d_roc.clone());
-						//dbg!(s_roc.clone());
-						
-						for i in 1..j_roc.len(){
-							j_auc += j_roc[i].0*(j_roc[i].1 - j_roc[i-1].1);
-							d_auc += d_roc[i].0*(d_roc[i].1 - d_roc[i-1].1);
-							s_auc += s_roc[i].0*(s_roc[i].1 - s_roc[i-1].1);
+			let alpha_split = Instant::now();
+			for _ in 0..args.reps {
+				let nodes_redacted = remove_attrs(nodes.clone(), (alpha as f64) / alpha_unit, &mut ctx.rng);
+				let network = attr_network(&nodes_redacted, &edges)?;
+
+				let num_observed = ((nodes.len() as f64) * (alpha as f64) / alpha_unit) as usize;
+				let mut baseline = if num_observed > 0 {
+					nodes_redacted.iter().map(|&x| x.attr).collect::<Vec<i32>>()
+				} else {
+					nodes.iter().map(|&x| x.attr).collect::<Vec<i32>>()
+				};
+				baseline.sort();
+				baseline.dedup();
+
+				let mut inferred = infer_attrs(&network, baseline, &mut ctx.rng);
+				let num_guessed = inferred.len() - num_observed;
+				inferred.sort_by_key(|a| a.node_id);
+				let num_wrong = inferred.iter().zip(nodes.iter()).filter(|(a, b)| a.attr != b.attr).count();
+				let acc = 1.0 - (num_wrong as f64) / (num_guessed as f64);
+
+				out.write_row(&[name.as_str().into(), ((alpha as f64) / alpha_unit).into(), acc.into()])?;
+			}
+			ctx.complete(&alpha_unit_name)?;
+			println!(
+				"alpha={} done in: {:.2?}, Total: {:.2?}",
+				alpha,
+				alpha_split.elapsed(),
+				ctx.start.elapsed()
+			);
+		}
+		ctx.complete(&unit)?;
+	}
+	Ok(())
+}
+
+fn link_predict(ctx: &mut Context, args: &LinkPredictArgs) -> anyhow::Result<()> {
+	let mut out = ctx.writer(
+		&args.output,
+		&["name", "alpha", "jaccard_auc", "degree_product_auc", "shortest_path_auc"],
+	)?;
+	let alpha_unit = args.steps as f64;
+	for i in 0..args.input.inputs.len() {
+		let unit = dataset_unit(&args.input, i);
+		if ctx.is_done(&unit) {
+			continue;
+		}
+		let (name, edge_list, nodes) = load_attr_nodes(&args.input, &args.attrs, i)?;
+		let edges = edge_list.edges;
+
+		let full_network = attr_network(&nodes, &edges)?;
+		let edges_as_pairs = full_network.edgelist();
+		let by_id: HashMap<u64, AttrNode> = nodes.iter().map(|&node| (node.node_id, node)).collect();
+
+		for alpha in 0..args.steps {
+			let alpha_unit_name = format!("{}/alpha/{}", unit, alpha);
+			if ctx.is_done(&alpha_unit_name) {
+				continue;
+			}
+			let alpha_split = Instant::now();
+			for rep in 0..args.reps {
+				// the edges left out of the observed sample are hidden from the full network
+				let observed: HashSet<(u64, u64)> = remove_edges(edges.clone(), (alpha as f64) / alpha_unit, &mut ctx.rng)
+					.into_iter()
+					.map(|edge| (edge.from, edge.to))
+					.collect();
+				let held_out = edges
+					.iter()
+					.filter(|edge| !observed.contains(&(edge.from, edge.to)))
+					.filter_map(|edge| Some((*by_id.get(&edge.from)?, *by_id.get(&edge.to)?)));
+				let network = EdgeMask::new(&full_network, held_out);
+
+				let j_roc = roc(jaccard_scores(&network, &mut ctx.rng), edges_as_pairs.clone());
+				let d_roc = roc(dp_scores(&network, &mut ctx.rng), edges_as_pairs.clone());
+				let s_roc = roc(sp_scores(&network, &mut ctx.rng), edges_as_pairs.clone());
+
+				if alpha == args.roc_step && rep == 0 {
+					for (file, curve) in [("hw3_jroc.csv", &j_roc), ("hw3_droc.csv", &d_roc), ("hw3_sroc.csv", &s_roc)] {
+						let mut roc_out = ctx.writer(Path::new(file), &["name", "tpr", "fpr"])?;
+						for item in curve {
+							roc_out.write_row(&[name.as_str().into(), item.0.into(), item.1.into()])?;
 						}
-		            	//dbg!(score_split.elapsed());
-		            	
-		            	//dbg!(inferred.clone());
-		            	let config_c_mgd = File::options()
-	                    .append(true)
-	                    .create(true)
-	                    .open("src/output/hw3_p2.csv")?;
-						
-						
-						//output
-						//dbg!(network.clone());
-	                	to_csv(&name, &[(alpha as f64)/alpha_unit,j_auc,d_auc,s_auc], &[], config_c_mgd)?;
 					}
-					let alpha_time = alpha_split.elapsed();
-            		let elapsed = now.elapsed();
-		           	println!("alpha={} done in: {:.2?}, Total: {:.2?}", alpha, alpha_time, elapsed);
-				}
-	            
-			}
-            //config model 1000x C and mgd
-            else if args.cnull_model {
-                //dbg!("output two open");
-                println!("Opening {}",filename_edges.clone());
-	            let edges_file = File::open(filename_edges.clone())?;
-	            
-	            let path = Path::new(&filename_edges);
-	            let name = path.file_stem().unwrap().to_str().unwrap();
-	            
-	            let edges = parse_edges(edges_file)?;
-	
-	            let nodes;
-				if filename_nodes != "" {
-					println!("Opening {}",filename_nodes.clone());
-					let nodes_file = File::open(filename_nodes.clone())?;
-					nodes = parse_basic_nodes(nodes_file)?;
-				} else {
-					nodes = edges.clone().iter().flat_map(|edge| [edge.from, edge.to]).collect::<Vec<u64>>();
 				}
-	            //dbg!(nodes.clone());
-	            //dbg!(edges.clone());
-                
-	            let mut network: SimpleNetwork<u64> = SimpleNetwork::from_node_vec(nodes);
-	            for edge in edges {
-	                network.add_edge(edge.from, edge.to)?;
-	            }
-	            //dbg!(network.clone());
-                
-                let reps = 1000;
-                let init = 10*network.total_edges;
-				let swaps = network.total_edges;
-                //baseline data
-                let config_c_mgd_one = File::options()
-                    .append(true)
-                    .create(true)
-                    .open("src/output/hw2_p4_data.csv")?;
-                let (mgd_actual,_,_) = network.mgd_diameter();
-				let cluster_actual = network.cluster_coeff();
-				//output to hw2_p4_data.csv
-				//dbg!(network.clone());
-               	to_csv(&name, &[cluster_actual,mgd_actual], &[], config_c_mgd_one)?;
-               	for _ in 0..init {
-					//double edge swap on network
-					network.double_edge_swap()?;
+
+				let mut j_auc = 0.0;
+				let mut d_auc = 0.0;
+				let mut s_auc = 0.0;
+				for i in 1..j_roc.len() {
+					j_auc += j_roc[i].0 * (j_roc[i].1 - j_roc[i - 1].1);
+					d_auc += d_roc[i].0 * (d_roc[i].1 - d_roc[i - 1].1);
+					s_auc += s_roc[i].0 * (s_roc[i].1 - s_roc[i - 1].1);
 				}
-				for _ in 0..reps {
-					let crg_split = Instant::now();
-					for _ in 0..swaps {
-						//double edge swap on network
-						network.double_edge_swap()?;
+				out.write_row(&[
+					name.as_str().into(),
+					((alpha as f64) / alpha_unit).into(),
+					j_auc.into(),
+					d_auc.into(),
+					s_auc.into(),
+				])?;
+			}
+			ctx.complete(&alpha_unit_name)?;
+			println!(
+				"alpha={} done in: {:.2?}, Total: {:.2?}",
+				alpha,
+				alpha_split.elapsed(),
+				ctx.start.elapsed()
+			);
+		}
+		ctx.complete(&unit)?;
+	}
+	Ok(())
+}
+
+/// Harmonic centrality of every node, with its significance against a null
+/// model if `--null-reps` is given. With `--spectral` the eigenvector, Katz
+/// and PageRank columns follow.
+fn centrality(ctx: &mut Context, args: &CentralityArgs) -> anyhow::Result<()> {
+	let columns: &[&str] = match args.spectral {
+		true => &["name", "node", "harmonic", "eigenvector", "katz", "pagerank"],
+		false => &["name", "node", "harmonic"],
+	};
+	let mut out = ctx.writer(&args.output, columns)?;
+	let convergence = Convergence { tolerance: args.tolerance, max_iterations: args.max_iterations };
+	let mut null_out = match args.null_reps {
+		0 => None,
+		_ => {
+			let columns: Vec<&str> = ["name", "node"].into_iter().chain(Significance::COLUMNS.iter().copied()).collect();
+			let significance_out = ctx.writer(&args.significance, &columns)?;
+			Some((ctx.writer(&args.null_output, &["name", "node", "draw", "harmonic"])?, significance_out))
+		}
+	};
+	for i in 0..args.input.inputs.len() {
+		let unit = dataset_unit(&args.input, i);
+		if ctx.is_done(&unit) {
+			continue;
+		}
+		let split = Instant::now();
+		let loaded = load(&args.input, i)?;
+		let network = &loaded.network;
+
+		let baselines = network.harmonic_centrality();
+		let spectral = match args.spectral {
+			true => spectral_centralities(args, &loaded, convergence)?,
+			false => vec![],
+		};
+		let mut nodes: Vec<u64> = baselines.keys().copied().collect();
+		nodes.sort();
+		for node in &nodes {
+			let mut row: Vec<Field> = vec![loaded.name.as_str().into(), loaded.label(*node).into(), baselines[node].into()];
+			for measure in &spectral {
+				row.push(measure.as_ref().map_or(f64::NAN, |values| values[node]).into());
+			}
+			out.write_row(&row)?;
+		}
+
+		if let Some((draws_out, significance_out)) = &mut null_out {
+			let mut ensemble = Ensemble::new(network, args.null_model.model(args.burn_in));
+			let mut draws: HashMap<u64, Vec<f64>> = nodes.iter().map(|&node| (node, vec![])).collect();
+			for _ in 0..args.null_reps {
+				for (node, r) in ensemble.sample(&mut ctx.rng)?.harmonic_centrality() {
+					if let Some(set) = draws.get_mut(&node) {
+						set.push(r);
 					}
-					let config_c_mgd = File::options()
-                    .append(true)
-                    .create(true)
-                    .open("src/output/hw2_p4_data.csv")?;
-					
-					//measure C and mgd
-					let (mgd,_,_) = network.mgd_diameter();
-					let cluster = network.cluster_coeff();
-					//output to hw2_p4_data.csv
-					//dbg!(network.clone());
-                	to_csv(&name, &[cluster,mgd], &[], config_c_mgd)?;
-                	let crg_time = crg_split.elapsed();
-            		let elapsed = now.elapsed();
-            		println!("CRG in: {:.2?}, Total: {:.2?}", crg_time, elapsed);
-					
 				}
-                println!(".");
 			}
-                        
-            //Watts-Strogatz + betweenness centrality
-            
-            
-            
-            let partial_time = split.elapsed();
-            let elapsed = now.elapsed();
-            println!("Split: {:.2?}, Total: {:.2?}", partial_time, elapsed);
-        }
-    } else if args.w_hw_num == 3 {
-		// read in (adjacency list)
-        let mut adj_data = vec![];
-        let mut filename = format!("src/data/{}.txt", args.name);
-		// filepath management, just getting all filenames from the folder
-		// should be in same position in vecs, unless something fails weirdly
-		// does assume that any *_attr.txt file has a corresponding *.txt
-        if args.folder {
-            let foldername = &format!("src/data/{}", args.name);
-            let paths = read_dir(foldername).unwrap();
-
-            for entry in paths {
-				let path_buf = entry?.path();
-				let path_str = path_buf.to_str().ok_or(anyhow!("non-utf8 path"))?;
-				
-				if let Some(extension) = path_buf.extension(){
-	                if extension == "txt"{
-	                    adj_data.push(path_str.to_owned());
-	                }
-                }
-            }
-        } else{
-			//dummy values so the for loop happens
-			adj_data.push(filename.clone());
-		}
-        for i in 0..adj_data.len() {
-			let split = Instant::now();
-			if args.folder {
-				filename = adj_data[i].clone();
+			for node in &nodes {
+				let (name, label) = (loaded.name.as_str(), loaded.label(*node));
+				for (draw, &r) in draws[node].iter().enumerate() {
+					draws_out.write_row(&[name.into(), label.as_str().into(), draw.into(), r.into()])?;
+				}
+				let mut row = vec![name.into(), label.into()];
+				row.extend(Significance::from_samples(baselines[node], &draws[node])?.fields());
+				significance_out.write_row(&row)?;
 			}
+		}
+		ctx.complete(&unit)?;
+		ctx.report(split);
+	}
+	Ok(())
+}
 
-            println!("Opening {}",filename.clone());
-            let adj_file = File::open(filename.clone())?;
-            
-            let path = Path::new(&filename);
-            let name = path.file_stem().unwrap().to_str().unwrap();
-            
-            let mut adjs = parse_adjacency_list(adj_file)?;
-            for adj in &mut adjs {
-				if(adj.degree == 0) {
-					adj.edges = vec![];
-				} else {
-					adj.edges = adj.edges.clone().into_iter().enumerate()
-      								.filter(|&(i, x)| (i + 1) % 2 != 0)
-     								.map(|(i, x)| x)
-     								.collect::<Vec<u64>>();
-     			}
+/// Eigenvector, Katz and PageRank centrality of `loaded`, in that order;
+/// `None` for a measure whose iteration did not converge.
+fn spectral_centralities(
+	args: &CentralityArgs,
+	loaded: &Loaded,
+	convergence: Convergence,
+) -> anyhow::Result<Vec<Option<HashMap<u64, f64>>>> {
+	let network = &loaded.network;
+	let converged = |result: hw5352::Result<HashMap<u64, f64>>| match result {
+		Ok(values) => Ok(Some(values)),
+		Err(Error::NoConvergence { .. }) => Ok(None),
+		Err(e) => Err(e),
+	};
+	let eigenvector = converged(network.eigenvector_centrality(convergence))?;
+	let alpha = match args.katz_alpha {
+		Some(alpha) => Some(alpha),
+		// without edges lambda_max is 0, and any alpha gives x = 1
+		None => match network.leading_eigenvalue(convergence) {
+			Ok(lambda) if lambda > 0.0 => Some(0.5 / lambda),
+			Ok(_) => Some(0.5),
+			Err(Error::NoConvergence { .. }) => None,
+			Err(e) => return Err(e.into()),
+		},
+	};
+	let katz = match alpha {
+		Some(alpha) => converged(network.katz_centrality(alpha, convergence))?,
+		None => None,
+	};
+	let personalization = if args.personalize.is_empty() {
+		None
+	} else {
+		let seeds = args.personalize.iter().map(|label| {
+			let node = loaded.node(label).ok_or_else(|| anyhow!("no node {} in {}", label, loaded.name))?;
+			Ok((node, 1.0))
+		});
+		Some(seeds.collect::<anyhow::Result<HashMap<u64, f64>>>()?)
+	};
+	let pagerank = converged(network.pagerank(args.damping, personalization.as_ref(), convergence))?;
+	Ok(vec![eigenvector, katz, pagerank])
+}
+
+/// Closeness of every node, or with `--top` just the best ranked. Harmonic
+/// centrality is included normalized within each component, to set against
+/// the whole-network normalization of `centrality`.
+fn closeness(ctx: &mut Context, args: &ClosenessArgs) -> anyhow::Result<()> {
+	let columns: &[&str] = match args.top {
+		Some(_) => &["name", "rank", "node", "wasserman_faust"],
+		None => &["name", "node", "reachable", "closeness", "wasserman_faust", "harmonic"],
+	};
+	let mut out = ctx.writer(&args.output, columns)?;
+	for i in 0..args.input.inputs.len() {
+		let unit = dataset_unit(&args.input, i);
+		if ctx.is_done(&unit) {
+			continue;
+		}
+		let split = Instant::now();
+		let loaded = load(&args.input, i)?;
+		let name = loaded.name.as_str();
+
+		match args.top {
+			Some(k) => {
+				for (rank, (node, score)) in loaded.network.top_k_closeness(k).into_iter().enumerate() {
+					out.write_row(&[name.into(), (rank + 1).into(), loaded.label(node).into(), score.into()])?;
+				}
 			}
-            
-			
-			let mut network: SimpleNetwork<u64> = SimpleNetwork::from_adj_list(adjs.clone());
-			
-			let reps = 1000;
-			let init = 10*network.total_edges;
-			let swaps = network.total_edges;
-			
-			
-            let mut full_stats = HashMap::new();
-            let baselines = network.harmonic_centrality();
-            for (&node,r) in baselines.iter() {
-				if let Some(source) = adjs.iter().find(|&adj| adj.node_id == node){
-					let medici_real = File::options()
-                    .append(true)
-                    .create(true)
-                    .open("src/output/hw2_medici_data.csv")?;
-					let node_name = source.name.clone();
-					to_csv(&node_name, &[*r], &[], medici_real)?;
+			None => {
+				let closeness = loaded.network.closeness();
+				let per_component = closeness.per_component();
+				let wasserman_faust = closeness.wasserman_faust();
+				let harmonic = closeness.harmonic_per_component();
+				for (i, &node) in closeness.nodes.iter().enumerate() {
+					out.write_row(&[
+						name.into(),
+						loaded.label(node).into(),
+						closeness.reachable[i].into(),
+						per_component[i].into(),
+						wasserman_faust[i].into(),
+						harmonic[i].into(),
+					])?;
 				}
-				full_stats.insert(node,vec![]);
 			}
-			
-			for _ in 0..init {
-				network.double_edge_swap();
+		}
+		ctx.complete(&unit)?;
+		ctx.report(split);
+	}
+	Ok(())
+}
+
+fn generate(ctx: &mut Context, args: &GenerateArgs) -> anyhow::Result<()> {
+	let max_nodes = args.nodes;
+	if max_nodes < 5 {
+		return Err(anyhow!("a ring lattice of degree 4 needs at least 5 nodes"));
+	}
+	let node_list: Vec<u64> = (0..max_nodes).collect();
+	let mut network_base: SimpleNetwork<u64> = SimpleNetwork::from_node_vec(node_list.clone());
+	for i in &node_list {
+		network_base.add_edge(*i, (*i + 1) % max_nodes)?;
+		network_base.add_edge(*i, (*i + 2) % max_nodes)?;
+	}
+
+	let mut out = ctx.writer(&args.output, &["name", "p", "node", "betweenness"])?;
+	for p in 0..=args.steps {
+		let unit = format!("p/{}", p);
+		if ctx.is_done(&unit) {
+			continue;
+		}
+		// rewire edges to new connections
+		let mut network = network_base.clone();
+		for i in &node_list {
+			if ctx.rng.gen_range(0..args.steps) < p {
+				network.rewire_edge_with_rng(*i, (*i + 1) % max_nodes, &mut ctx.rng)?;
 			}
-			for i in 0..reps {
-				for j in 0..swaps {
-					network.double_edge_swap();
-				}
-				let rand_data = network.harmonic_centrality();
-	            for (node,r) in rand_data.iter() {
-					if let Some(set) = full_stats.get_mut(node) {
-						set.push(*r);
-					}
-				}
+			if ctx.rng.gen_range(0..args.steps) < p {
+				network.rewire_edge_with_rng(*i, (*i + 2) % max_nodes, &mut ctx.rng)?;
 			}
-			
-			for (&node,set) in full_stats.iter() {
-					if let Some(source) = adjs.iter().find(|&adj| adj.node_id == node){
-						let medici_rand = File::options()
-	                    .append(true)
-	                    .create(true)
-	                    .open("src/output/hw2_medici_dist.csv")?;
-						let node_name = source.name.clone();
-						to_csv(&node_name, &set, &[], medici_rand)?;
-					}
-				}
-			
-            let partial_time = split.elapsed();
-            let elapsed = now.elapsed();
-            println!("Split: {:.2?}, Total: {:.2?}", partial_time, elapsed);
-        }
+		}
+		let betweenness = network.betweenness_centrality();
+		for node in &node_list {
+			out.write_row(&["WStest".into(), ((p as f64) / (args.steps as f64)).into(), (*node).into(), betweenness[node].into()])?;
+		}
+		ctx.complete(&unit)?;
+	}
+	Ok(())
+}
+
+/// Expands a spec and runs each point of its grid under its own run id.
+///
+/// Every run is parsed up front, so a bad spec fails before anything runs.
+/// The resolved runs, with all defaults filled in, are written next to the
+/// outputs as `<spec>.resolved.json`. Run `i` is seeded with `seed + i + 1`,
+/// from the spec run's own seed; with `--checkpoint`, finished runs are skipped on
+/// resume and each run keeps its own checkpoint next to the spec's.
+fn run_spec(ctx: &mut Context, args: &RunArgs) -> anyhow::Result<()> {
+	let spec = ExperimentSpec::load(&args.spec)?;
+	let out_dir = spec.output_dir.clone().unwrap_or_else(|| ctx.out_dir.clone());
+
+	let mut runs = vec![];
+	for (i, config) in spec.resolve()?.into_iter().enumerate() {
+		let command_line = config.to_args();
+		let command = SpecRun::try_parse_from(&command_line)
+			.map_err(|e| anyhow!("experiment {}: {}", config.experiment, e))?
+			.command;
+		if let Command::Run(_) = command {
+			return Err(anyhow!("experiment {}: specs cannot run other specs", config.experiment));
+		}
+		let seed = ctx.run.seed.map(|seed| seed.wrapping_add(i as u64 + 1));
+		let mut run = RunMetadata::new(seed)
+			.with_parameter("experiment", &config.experiment)
+			.with_parameter("replicate", config.replicate)
+			.with_parameter("command", command_line.join(" "));
+		for (name, value) in &config.parameters {
+			run = run.with_parameter(name, value);
+		}
+		run.run_id = format!("{}.{}", ctx.run.run_id, i);
+		runs.push((config, command, run));
+	}
+
+	if args.dry_run {
+		for (_, _, run) in &runs {
+			println!("{}: {}", run.run_id, run.parameters["command"]);
+		}
+		return Ok(());
+	}
+
+	create_dir_all(&out_dir)?;
+	let resolved = json!({
+		"spec": args.spec,
+		"run_id": ctx.run.run_id,
+		"output_dir": out_dir,
+		"runs": runs
+			.iter()
+			.map(|(config, command, run)| {
+				json!({
+					"run_id": run.run_id,
+					"experiment": config.experiment,
+					"replicate": config.replicate,
+					"parameters": config.parameters,
+					"resolved": command,
+				})
+			})
+			.collect::<Vec<_>>(),
+	});
+	let spec_name = args.spec.file_stem().and_then(|s| s.to_str()).unwrap_or("experiment");
+	serde_json::to_writer_pretty(File::create(out_dir.join(format!("{}.resolved.json", spec_name)))?, &resolved)?;
+
+	let total = runs.len();
+	for (i, (_, command, run)) in runs.into_iter().enumerate() {
+		let unit = format!("run/{}", i);
+		if ctx.is_done(&unit) {
+			continue;
+		}
+		println!("Run {}/{}: {}", i + 1, total, run.parameters["command"]);
+		let checkpoint = ctx
+			.checkpoint
+			.as_ref()
+			.map(|c| PathBuf::from(format!("{}.{}", c.path().display(), i)));
+		let key = json!({ "output_dir": out_dir, "command": command }).to_string();
+		let mut run_ctx = Context::new(out_dir.clone(), run, checkpoint, &key)?;
+		run_ctx.start = ctx.start;
+		run_ctx.run.record(&out_dir)?;
+		execute(&mut run_ctx, command)?;
+		run_ctx.finish()?;
+		ctx.complete(&unit)?;
+	}
+	Ok(())
+}
+
+/// Runs `command`, with any folder among its inputs expanded first.
+fn execute(ctx: &mut Context, mut command: Command) -> anyhow::Result<()> {
+	if let Some(input) = command.input_mut() {
+		*input = input.expand_folders()?;
+	}
+	match &command {
+		Command::Stats(args) => stats(ctx, args),
+		Command::Distances(args) => distances(ctx, args),
+		Command::FastDistances(args) => fast_distances(ctx, args),
+		Command::Estimate(args) => estimate(ctx, args),
+		Command::Components(args) => components(ctx, args),
+		Command::Clustering(args) => clustering(ctx, args),
+		Command::Assortativity(args) => assortativity(ctx, args),
+		Command::Homophily(args) => homophily(ctx, args),
+		Command::Degrees(args) => degrees(ctx, args),
+		Command::NullModel(args) => null_model(ctx, args),
+		Command::SbmFit(args) => sbm_fit(ctx, args),
+		Command::InferAttrs(args) => infer_attributes(ctx, args),
+		Command::LinkPredict(args) => link_predict(ctx, args),
+		Command::Centrality(args) => centrality(ctx, args),
+		Command::Closeness(args) => closeness(ctx, args),
+		Command::Generate(args) => generate(ctx, args),
+		Command::Run(args) => run_spec(ctx, args),
 	}
+}
+
+fn main() -> anyhow::Result<()> {
+	let cli = Cli::parse();
+	create_dir_all(&cli.output_dir)?;
+	if let Some(threads) = cli.threads {
+		rayon::ThreadPoolBuilder::new().num_threads(threads).build_global()?;
+	}
+
+	let command = std::env::args().skip(1).collect::<Vec<_>>().join(" ");
+	let run = RunMetadata::new(cli.seed).with_parameter("command", command);
+	let key = json!({ "output_dir": cli.output_dir, "command": cli.command }).to_string();
+	let mut ctx = Context::new(cli.output_dir.clone(), run, cli.checkpoint.clone(), &key)?;
+	ctx.run.record(&cli.output_dir)?;
+
+	execute(&mut ctx, cli.command)?;
+	ctx.finish()?;
 
-    let elapsed = now.elapsed();
-    println!("Completed in: {:.2?}", elapsed);
-    Ok(())
+	println!("Completed in: {:.2?}", ctx.start.elapsed());
+	Ok(())
 }
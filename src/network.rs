@@ -1,322 +1,506 @@
-use std::collections::{HashSet, HashMap, BinaryHeap};
-use std::cmp::{Reverse,max};
-use anyhow::anyhow;
+use std::collections::{HashSet, HashMap};
 use rand::Rng;
-use std::time::Instant;
 
 //TODO: finish documentation for hw2/3
 
+use crate::error::{Error, Result};
+use crate::graph::Graph;
 use crate::hw1::Adjacency;
 use crate::node::Node;
 
 /// A simple network, represented as an adjacency list.
 ///
+/// The fields are kept consistent by the methods that change them: every
+/// edge is stored in both endpoints' sets and once in the edge list, and
+/// there are no self loops. See [`validate`](Self::validate).
+///
+/// The node and edge lists are kept in the order they were built, so random
+/// draws by position (in [`rewire_edge_with_rng`](Self::rewire_edge_with_rng)
+/// and [`double_edge_swap_with_rng`](Self::double_edge_swap_with_rng)) give
+/// the same result for the same seed and the same sequence of edits.
 #[derive(Debug,Clone)]
 pub struct SimpleNetwork<N: Node = u64>{
-    pub nodes: HashSet<N>,
-    adjacencies: HashMap<N, HashSet<N>>,
-    pub total_edges: u64,
+	/// Every node, in the order added (sorted for those given to
+	/// [`from_node_vec`](Self::from_node_vec))
+	nodes: Vec<N>,
+	adjacencies: HashMap<N, HashSet<N>>,
+	/// Every edge once, as `(u, v)` with `u < v`
+	edges: Vec<(N, N)>,
+	/// Position of each edge in `edges`
+	edge_index: HashMap<(N, N), usize>,
+}
+
+/// How many draws per edge [`SimpleNetwork::double_edge_swap_with_rng`] makes
+/// before deciding that no swap is possible
+const SWAP_ATTEMPTS_PER_EDGE: usize = 100;
+
+/// The key of an undirected edge in the edge list
+fn edge_key<N: Node>(from: N, to: N) -> (N, N) {
+	if from < to { (from,to) } else { (to,from) }
 }
 
 impl<N: Node> SimpleNetwork<N> {
-	pub fn from_node_vec(node_vec: Vec<N>) -> Self {
+	pub fn from_node_vec(mut node_vec: Vec<N>) -> Self {
+		node_vec.sort();
+		node_vec.dedup();
 		Self {
-			nodes: HashSet::from_iter(node_vec.clone().into_iter()),
-			adjacencies: HashMap::from_iter(node_vec.clone().into_iter().map(|n| (n,HashSet::default()))),
-			total_edges: 0,
+			adjacencies: HashMap::from_iter(node_vec.iter().map(|&n| (n,HashSet::default()))),
+			nodes: node_vec,
+			edges: vec![],
+			edge_index: HashMap::new(),
 		}
 	}
 	
-	pub fn from_adj_list(adj_list: Vec<Adjacency<N>>) -> Self {
-		Self {
-			nodes: HashSet::from_iter(adj_list.clone().into_iter().map(|adj| adj.node_id)),
-			adjacencies: HashMap::from_iter(adj_list.clone().into_iter().map(|adj| (adj.node_id,adj.edges.into_iter().collect::<HashSet<N>>()))),
-			total_edges: adj_list.clone().into_iter().fold(0, |cnt, adj| cnt + adj.degree),
+	/// Builds a network from its nodes and a list of edges between them.
+	///
+	/// Unlike [`from_adj_list`](Self::from_adj_list) nothing is skipped: an
+	/// edge listed twice, a self loop or an unknown end is an error.
+	pub fn from_edges<I: IntoIterator<Item = (N, N)>>(node_vec: Vec<N>, edges: I) -> Result<Self> {
+		let mut network = Self::from_node_vec(node_vec);
+		for (from,to) in edges {
+			network.add_edge(from,to)?;
 		}
+		Ok(network)
 	}
 	
-	pub fn add_edge(&mut self, from: N, to: N) -> anyhow::Result<()>{
-		//dbg!(from,to);
-		let from_set = self.adjacencies.get_mut(&from).ok_or(anyhow!("edge fail"))?;
-		if !from_set.contains(&to) {
-			self.total_edges += 1;
-		}
-		from_set.insert(to);
-		
-		let to_set = self.adjacencies.get_mut(&to).ok_or(anyhow!("edge fail"))?;
-		if !to_set.contains(&from) {
-			self.total_edges += 1;
+	/// Builds a network from adjacency records.
+	///
+	/// Edges are taken from the neighbour lists, not the declared `degree`,
+	/// and are added in both directions whether they are listed once or
+	/// twice. Self loops are skipped; a neighbour that has no record of its
+	/// own is an [`Error::UnknownNode`].
+	pub fn from_adj_list(adj_list: Vec<Adjacency<N>>) -> Result<Self> {
+		let mut network = Self::from_node_vec(adj_list.iter().map(|adj| adj.node_id).collect());
+		for adj in adj_list {
+			for nbr in adj.edges {
+				match network.add_edge(adj.node_id,nbr) {
+					Ok(()) | Err(Error::SelfLoop(_)) | Err(Error::DuplicateEdge{..}) => {}
+					Err(e) => return Err(e),
+				}
+			}
 		}
-		to_set.insert(from);
-		Ok(())
+		Ok(network)
+	}
+	
+	pub fn num_nodes(&self) -> usize {
+		self.nodes.len()
+	}
+	
+	/// Number of (undirected) edges
+	pub fn num_edges(&self) -> u64 {
+		self.edges.len() as u64
 	}
 	
-	pub fn remove_edge(&mut self, from: N, to: N) -> anyhow::Result<()>{
-		let from_set = self.adjacencies.get_mut(&from).ok_or(anyhow!("edge not present"))?;
-		if from_set.contains(&to) {
-			self.total_edges -= 2;
+	/// The nodes, in the order they were added
+	pub fn nodes(&self) -> impl Iterator<Item = N> + '_ {
+		self.nodes.iter().copied()
+	}
+	
+	pub fn has_node(&self, node: N) -> bool {
+		self.adjacencies.contains_key(&node)
+	}
+	
+	pub fn has_edge(&self, from: N, to: N) -> bool {
+		self.adjacencies.get(&from).is_some_and(|adj| adj.contains(&to))
+	}
+	
+	/// Number of neighbours of `node`; 0 if it is not in the network (use
+	/// [`has_node`](Self::has_node) to tell the two apart)
+	pub fn degree(&self, node: N) -> usize {
+		self.adjacencies.get(&node).map_or(0, |adj| adj.len())
+	}
+	
+	/// The neighbours of `node`, in no particular order; none if it is not
+	/// in the network
+	pub fn neighbors(&self, node: N) -> impl Iterator<Item = N> + '_ {
+		self.adjacencies.get(&node).into_iter().flatten().copied()
+	}
+	
+	/// Adds an isolated node, returning false if it was already present.
+	pub fn add_node(&mut self, node: N) -> bool {
+		if self.adjacencies.contains_key(&node) {
+			false
+		} else {
+			self.nodes.push(node);
+			self.adjacencies.insert(node, HashSet::default());
+			true
 		}
-		from_set.remove(&to);
-		self.adjacencies.get_mut(&to).ok_or(anyhow!("edge not present"))?.remove(&from);
-		Ok(())
 	}
 	
-	//randomly rewire specified edge, maintaining from point
-	pub fn rewire_edge(&mut self, from: N, to: N) -> anyhow::Result<()> {
-		let from_set = self.adjacencies.get_mut(&from).ok_or(anyhow!("edge not present"))?;
-		if from_set.contains(&to) {
-			let mut new_dest = to;
-			while from_set.contains(&new_dest){
-				let rand_idx = rand::thread_rng().gen_range(0..self.nodes.len()) as usize;
-				new_dest = self.nodes.clone().into_iter().collect::<Vec<N>>()[rand_idx];
-			}
-			from_set.remove(&to);
-			from_set.insert(new_dest);
-			self.adjacencies.get_mut(&to).ok_or(anyhow!("edge not present"))?.remove(&from);
-			self.adjacencies.get_mut(&new_dest).ok_or(anyhow!("edge not present"))?.remove(&from);
-
+	/// Removes a node together with its edges.
+	pub fn remove_node(&mut self, node: N) -> Result<()> {
+		let adj = self.adjacencies.get(&node).ok_or(Error::UnknownNode(node.id()))?;
+		// in a fixed order, so that the edge list ends up the same every time
+		let mut nbrs: Vec<N> = adj.iter().copied().collect();
+		nbrs.sort();
+		for nbr in nbrs {
+			self.remove_edge(node,nbr)?;
+		}
+		self.adjacencies.remove(&node);
+		if let Some(pos) = self.nodes.iter().position(|&n| n == node) {
+			self.nodes.remove(pos);
 		}
 		Ok(())
 	}
 	
-	/// Performs a random double edge swap on the graph
-	/// 
-	/// Ensures that the resulting graph is still a valid simple graph, and that 
-	/// the degree of each node remains the same.
-	pub fn double_edge_swap(&mut self) -> anyhow::Result<()>{
-		let mut rewired = false;
-		let mut flat_edges = vec![];
-		self.adjacencies.clone().iter().for_each(|(node_1, edges)| {
-			edges.clone().iter().for_each(|node_2| {
-				//dbg!((*node_1,*node_2));
-				flat_edges.push((*node_1,*node_2))
-			})
-		});
-		//dbg!(flat_edges.len());
-		//dbg!(self.total_edges);
-		//dbg!("trying swap");
-		while !rewired {
-			//dbg!("trying again");
-			let choice_1 = rand::thread_rng().gen_range(0..self.total_edges) as usize;
-			let choice_2 = rand::thread_rng().gen_range(0..self.total_edges) as usize;
-			let (u,v) = flat_edges[choice_1];
-			let (x,y) = flat_edges[choice_2];
-			if let Some(u_adj) = self.adjacencies.get(&u){
-				if let Some(v_adj) = self.adjacencies.get(&v){
-					if !u_adj.contains(&x) && !v_adj.contains(&y) && u != x && v != y{
-						self.remove_edge(u,v)?;
-						self.remove_edge(x,y)?;
-						self.add_edge(u,x)?;
-						self.add_edge(v,y)?;
-						rewired = true;
-					}
+	/// Checks the invariants the other methods maintain: the node list matches
+	/// the adjacency lists, every neighbour is a node, edges are stored in both
+	/// directions and in the edge list, and there are no self loops.
+	///
+	/// The first problem found is returned, e.g. [`Error::MissingEdge`] for
+	/// the missing direction of a one-sided edge.
+	pub fn validate(&self) -> Result<()> {
+		if self.nodes.len() != self.adjacencies.len() {
+			return Err(Error::InvalidData(format!("{} nodes but {} adjacency lists", self.nodes.len(), self.adjacencies.len())));
+		}
+		let listed: HashSet<N> = self.nodes.iter().copied().collect();
+		let mut degree_sum = 0;
+		for (node, adj) in &self.adjacencies {
+			if !listed.contains(node) {
+				return Err(Error::UnknownNode(node.id()));
+			}
+			for nbr in adj {
+				if nbr == node {
+					return Err(Error::SelfLoop(node.id()));
+				}
+				if !self.adjacencies.contains_key(nbr) {
+					return Err(Error::UnknownNode(nbr.id()));
+				}
+				if !self.has_edge(*nbr,*node) {
+					return Err(Error::MissingEdge{from: nbr.id(), to: node.id()});
+				}
+				if self.edge_index.get(&edge_key(*node,*nbr)).is_none_or(|&i| self.edges[i] != edge_key(*node,*nbr)) {
+					return Err(Error::InvalidData(format!("edge {}-{} is missing from the edge list", node, nbr)));
 				}
 			}
+			degree_sum += adj.len();
+		}
+		if degree_sum != 2*self.edges.len() || self.edge_index.len() != self.edges.len() {
+			return Err(Error::InvalidData(format!("degrees sum to {} but the edge list has {} edges", degree_sum, self.edges.len())));
 		}
 		Ok(())
 	}
-	
-	/// Computes mean square-degree
-	pub fn mean_square_degree(&self) -> f64{
-		(self.adjacencies.clone().into_values().fold(0,|sum,x| sum+x.len().pow(2)) as f64)/(self.nodes.len() as f64)
-	}
-	
-	pub fn mean_degree_empirical(&self) -> f64{
-		(self.adjacencies.clone().into_values().fold(0,|sum,x| sum+x.len()) as f64)/(self.nodes.len() as f64)
+
+	/// Adds an undirected edge between two nodes already in the network.
+	///
+	/// Fails with [`Error::UnknownNode`] if either end is missing, and with
+	/// [`Error::SelfLoop`] or [`Error::DuplicateEdge`] if the edge would make
+	/// the network non-simple.
+	pub fn add_edge(&mut self, from: N, to: N) -> Result<()>{
+		self.check_node(from)?;
+		self.check_node(to)?;
+		if from == to {
+			return Err(Error::SelfLoop(from.id()));
+		}
+		if self.has_edge(from,to) {
+			return Err(Error::DuplicateEdge{from: from.id(), to: to.id()});
+		}
+		self.insert_edge(from,to);
+		Ok(())
 	}
 	
-	/// SSSP using BFS for simple graphs
-	///
-	/// Starting from a given vertex, builds a `HashMap` keyed by vertex
-	/// storing the distance from source to that vertex.  If a vertex is unreachable, 
-	/// it is not in the map.
-	/// 
-	/// Should take O(V+E) time
-	pub fn sssp(&self,from: N) -> HashMap<N, i64> {
-		// Map to return, and queue (min heap) for next vertices to check
-		let mut dists = HashMap::new();
-		let mut queue = BinaryHeap::<(Reverse<i64>,N)>::new();
-		dists.insert(from,0);
-		queue.push((Reverse(0),from));
-		while !queue.is_empty() {
-			// Pop a vertex from the queue, then check for any neighbors we haven't seen
-			// The distances are all +1 from previous vertex
-			if let Some((Reverse(base_dist), next)) = queue.pop(){
-				if let Some(adj) = self.adjacencies.get(&next){
-					adj.iter().for_each(|node| if !dists.contains_key(node) {
-												dists.insert(*node,base_dist+1);
-												queue.push((Reverse(base_dist+1),*node));});
-				}
+	/// Adds an edge the caller knows to be new, between two distinct nodes
+	/// of the network
+	fn insert_edge(&mut self, from: N, to: N) {
+		for (a,b) in [(from,to),(to,from)] {
+			if let Some(adj) = self.adjacencies.get_mut(&a) {
+				adj.insert(b);
 			}
 		}
-		dists
+		self.edge_index.insert(edge_key(from,to), self.edges.len());
+		self.edges.push(edge_key(from,to));
 	}
 	
-	//may need APSP for some stats?
-	
-	/// Finds the largest connected component in a simple graph by
-	/// checking SSSP repeatedly from different sources until all nodes
-	/// have been found.
-	/// 
-	/// Returns a `HashSet` containing each node from that component.
-	/// 
-	/// Should take O(V+E) time in total
-	pub fn largest_component(&self) -> HashSet<N> {
-		// track unreached nodes and current largest component
-		let mut unreached_nodes = self.nodes.clone();
-		let mut largest = HashMap::new();
-		while !unreached_nodes.is_empty() {
-			let next_src = unreached_nodes.iter().next().unwrap().clone();
-			let component = self.sssp(next_src);
-			// remove the found nodes, then update largest
-			component.clone().into_keys().for_each(|node| {unreached_nodes.remove(&node);});
-			if component.len() > largest.len(){
-				largest = component;
+	/// Removes an edge, failing with [`Error::MissingEdge`] if it is not present.
+	pub fn remove_edge(&mut self, from: N, to: N) -> Result<()>{
+		self.check_node(from)?;
+		self.check_node(to)?;
+		if let Some(from_set) = self.adjacencies.get_mut(&from) {
+			if !from_set.remove(&to) {
+				return Err(Error::MissingEdge{from: from.id(), to: to.id()});
 			}
 		}
-		// Convert `HashMap` (from SSSP) to `HashSet`
-		largest.into_keys().collect::<HashSet<N>>()
+		if let Some(to_set) = self.adjacencies.get_mut(&to) {
+			to_set.remove(&from);
+		}
+		// the last edge takes its place in the list
+		if let Some(pos) = self.edge_index.remove(&edge_key(from,to)) {
+			self.edges.swap_remove(pos);
+			if let Some(&moved) = self.edges.get(pos) {
+				self.edge_index.insert(moved, pos);
+			}
+		}
+		Ok(())
 	}
 	
-	/// Computes max and mean shortest paths (aka diameter and mean
-	/// geodesic distance respectvely) of the largest connected component.
-	/// Uses SSSP from each vertex rather than APSP as it should be faster/easier
-	/// on simple graphs, despite the double counting. Additionally returns the
-	/// size of the largest component used.
-	/// 
-	/// Should take O(V^2+VE) time
-	pub fn mgd_diameter(&self) -> (f64,u64,u64) {
-		let component = self.largest_component();
-		// double fold summing over the whole adjacency matrix, but we get to throw out each row as we use it.
-		let (mgd, diameter) = component.iter().fold((0.0,0), |(sum,maximum),node| {
-			self.sssp(*node).into_values().fold((sum,maximum), |(sum_part,max_part),node| {
-				(sum_part+(node as f64),max(max_part,node))
-			})
-		});
-		// no factor of two here because each path was counted twice - a little misleading name but works
-		let paths = component.len()*(component.len()-1);
-		(mgd/(paths as f64),diameter as u64,component.len() as u64)
-	}
-	
-	/// Computes the clustering coefficient for the graph
-	pub fn cluster_coeff(&self) -> f64 {
-		let (triads,triangles) = self.adjacencies.clone().into_values().fold((0,0),|(triad_count,triangle_count),edges| {
-			(triad_count + edges.len()*(edges.len()-1), edges.clone().iter().fold(triangle_count,|prev_count,node_1| {
-				if let Some(next_steps) = self.adjacencies.get(node_1) {
-					prev_count + edges.intersection(next_steps).count()
-				} else {
-					prev_count
-				}
-			}))
-		});
-		(triangles as f64)/(triads as f64)
+	/// Replaces the edge at `pos` in the edge list with `from`-`to`, keeping
+	/// its position. The adjacency sets are up to the caller.
+	fn replace_listed_edge(&mut self, pos: usize, from: N, to: N) {
+		self.edge_index.remove(&self.edges[pos]);
+		self.edges[pos] = edge_key(from,to);
+		self.edge_index.insert(self.edges[pos], pos);
 	}
 	
-	pub fn harmonic_centrality(&self) -> HashMap<N,f64> {
-		let mut result = HashMap::new();
-		for node in self.nodes.clone().iter() {
-			let total = self.sssp(*node).into_values().fold(0.0,|prev,d| if(d != 0){prev + (1.0/(d as f64))} else {prev});
-			result.insert(*node,total/(self.nodes.len() as f64-1.0));
+	//randomly rewire specified edge, maintaining from point
+	pub fn rewire_edge(&mut self, from: N, to: N) -> Result<()> {
+		self.rewire_edge_with_rng(from, to, &mut rand::thread_rng())
+	}
+
+	/// [`rewire_edge`](Self::rewire_edge), drawing from the given RNG
+	///
+	/// Fails with [`Error::InvalidParameter`] if `from` is already linked to
+	/// every other node, so there is nowhere to rewire to.
+	pub fn rewire_edge_with_rng<R: Rng + ?Sized>(&mut self, from: N, to: N, rng: &mut R) -> Result<()> {
+		self.check_node(to)?;
+		let from_set = self.adjacencies.get_mut(&from).ok_or(Error::UnknownNode(from.id()))?;
+		if !from_set.contains(&to) {
+			return Err(Error::MissingEdge{from: from.id(), to: to.id()});
+		}
+		// the new end must not be a neighbour already, nor `from` itself
+		if from_set.len() + 1 >= self.nodes.len() {
+			return Err(Error::InvalidParameter(format!("node {} is linked to every other node, so its edge cannot be rewired", from)));
+		}
+		let mut new_dest = to;
+		while new_dest == from || from_set.contains(&new_dest){
+			new_dest = self.nodes[rng.gen_range(0..self.nodes.len())];
+		}
+		from_set.remove(&to);
+		from_set.insert(new_dest);
+		if let Some(to_set) = self.adjacencies.get_mut(&to) {
+			to_set.remove(&from);
+		}
+		if let Some(dest_set) = self.adjacencies.get_mut(&new_dest) {
+			dest_set.insert(from);
+		}
+		if let Some(&pos) = self.edge_index.get(&edge_key(from,to)) {
+			self.replace_listed_edge(pos, from, new_dest);
 		}
-		result
+		Ok(())
 	}
 	
-	//also return paths
-	pub fn sssp_verbose(&self,from: N) -> HashMap<N, Vec<N>> {
-		// Map to store in-progress paths, from all nodes
-		let mut paths = HashMap::new();
-		for node in self.nodes.clone() {
-			paths.insert(node, vec![]);
+	fn check_node(&self, node: N) -> Result<()> {
+		if self.adjacencies.contains_key(&node) {
+			Ok(())
+		} else {
+			Err(Error::UnknownNode(node.id()))
 		}
-		// Map to return, and queue (min heap) for next vertices to check
-		let mut dists = HashMap::new();
-		let mut queue = BinaryHeap::<(Reverse<i64>,N)>::new();
-		dists.insert(from,vec![]);
-		queue.push((Reverse(0),from));
-		while !queue.is_empty() {
-			// Pop a vertex from the queue, then check for any neighbors we haven't seen
-			// The distances are all +1 from previous vertex
-			if let Some((Reverse(base_dist), next)) = queue.pop(){
-				if let Some(adj) = self.adjacencies.get(&next){
-					adj.iter().for_each(|node| if !dists.contains_key(node) {
-												if let Some(pvec) = paths.get_mut(&node){
-													pvec.push(next);
-												}
-												if let Some(pvec) = paths.get(&node){
-													dists.insert(*node,pvec.clone());
+	}
+	
+	/// Performs a random double edge swap on the graph
+	/// 
+	/// Ensures that the resulting graph is still a valid simple graph, and that 
+	/// the degree of each node remains the same.
+	pub fn double_edge_swap(&mut self) -> Result<()>{
+		self.double_edge_swap_with_rng(&mut rand::thread_rng())
+	}
 
-												}
-												queue.push((Reverse(base_dist+1),*node));});
+	/// [`double_edge_swap`](Self::double_edge_swap), drawing from the given RNG
+	///
+	/// With a seedable RNG the chain can be checkpointed and resumed. Each
+	/// attempt costs O(1): the two edges are drawn by position in the edge
+	/// list, which the swap updates in place.
+	///
+	/// Some graphs (a star, a triangle) have no valid swap at all, so after
+	/// 100 draws per edge without one this gives up with
+	/// [`Error::InvalidParameter`].
+	pub fn double_edge_swap_with_rng<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Result<()>{
+		let m = self.edges.len();
+		if m < 2 {
+			return Err(Error::InvalidParameter(format!("a double edge swap needs at least 2 edges, found {}", m)));
+		}
+		for _ in 0..SWAP_ATTEMPTS_PER_EDGE*m {
+			// each edge in either direction, as if drawn from a list of both
+			let choice_1 = rng.gen_range(0..2*m);
+			let choice_2 = rng.gen_range(0..2*m);
+			let (i,j) = (choice_1/2, choice_2/2);
+			let (u,v) = if choice_1 % 2 == 0 { self.edges[i] } else { (self.edges[i].1,self.edges[i].0) };
+			let (x,y) = if choice_2 % 2 == 0 { self.edges[j] } else { (self.edges[j].1,self.edges[j].0) };
+			// u-v, x-y become u-x, v-y
+			if u != x && v != y && !self.has_edge(u,x) && !self.has_edge(v,y) {
+				for (a,b,c) in [(u,v,x),(v,u,y),(x,y,u),(y,x,v)] {
+					if let Some(adj) = self.adjacencies.get_mut(&a) {
+						adj.remove(&b);
+						adj.insert(c);
+					}
 				}
+				self.replace_listed_edge(i, u, x);
+				self.replace_listed_edge(j, v, y);
+				return Ok(());
 			}
 		}
-		dists
+		Err(Error::InvalidParameter(format!("no valid double edge swap found in {} attempts", SWAP_ATTEMPTS_PER_EDGE*m)))
 	}
 	
-	pub fn betweenness_centrality(&self) -> HashMap<N,u64> {
-		let mut result = HashMap::new();
-		for node in self.nodes.clone() {
-			result.insert(node, 0);
-		}
-		//check per node's sssp
-		for node in self.nodes.clone().iter() {
-			self.sssp_verbose(*node).into_values().for_each(|path| path.iter()
-												.for_each(|i| if let Some(count) = result.get_mut(&i){
-																		*count = &*count+1;
-																		}));
+	/// The subgraph made of `nodes` (those present in this network) and all
+	/// edges between them
+	pub fn induced_subgraph<I: IntoIterator<Item = N>>(&self, nodes: I) -> Self {
+		let keep: HashSet<N> = nodes.into_iter().filter(|n| self.adjacencies.contains_key(n)).collect();
+		let mut subgraph = Self::from_node_vec(keep.iter().copied().collect());
+		for node in subgraph.nodes.clone() {
+			if let Some(adj) = self.adjacencies.get(&node) {
+				let mut inside: Vec<N> = adj.iter().copied().filter(|nbr| node < *nbr && keep.contains(nbr)).collect();
+				inside.sort();
+				for nbr in inside {
+					subgraph.insert_edge(node,nbr);
+				}
+			}
 		}
-		result
-		
+		subgraph
 	}
 	
 	pub fn get_adjs(&self, node: N) -> Option<&HashSet<N>> {
 		self.adjacencies.get(&node)
 	}
+}
+
+impl<N: Node> Graph for SimpleNetwork<N> {
+	type Node = N;
+	
+	fn nodes(&self) -> impl Iterator<Item = N> + '_ {
+		SimpleNetwork::nodes(self)
+	}
+	
+	fn neighbors(&self, node: N) -> impl Iterator<Item = N> + '_ {
+		SimpleNetwork::neighbors(self, node)
+	}
+	
+	fn has_node(&self, node: N) -> bool {
+		SimpleNetwork::has_node(self, node)
+	}
 	
-	pub fn get_nonedges(&self) -> HashSet<(N,N)> {
-		let mut pairs = HashSet::new();
-		for src in self.nodes.clone() {
-			//let split = Instant::now();
-			for dest in self.nodes.clone() {
-				if let Some(adjs) = self.adjacencies.get(&src) {
-								//let split2 = Instant::now();
+	fn num_nodes(&self) -> usize {
+		SimpleNetwork::num_nodes(self)
+	}
+	
+	fn num_edges(&self) -> u64 {
+		SimpleNetwork::num_edges(self)
+	}
+	
+	fn degree(&self, node: N) -> usize {
+		SimpleNetwork::degree(self, node)
+	}
+	
+	fn has_edge(&self, from: N, to: N) -> bool {
+		SimpleNetwork::has_edge(self, from, to)
+	}
+	
+	fn edges(&self) -> impl Iterator<Item = (N, N)> + '_ {
+		self.edges.iter().copied()
+	}
+}
 
-					if src != dest && !adjs.contains(&dest) {
-						pairs.insert((src,dest));
-					}
-								//dbg!(split2.elapsed());
+/// A `G(n, p)` random graph on the nodes `0..n`, drawn from a seeded RNG
+/// so that tests see the same graph every run
+#[cfg(test)]
+pub(crate) fn random_network(n: u64, p: f64, seed: u64) -> SimpleNetwork {
+	use rand::SeedableRng;
+	
+	let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(seed);
+	let pairs: Vec<(u64,u64)> = (0..n).flat_map(|a| (a+1..n).map(move |b| (a,b))).collect();
+	let edges: Vec<(u64,u64)> = pairs.into_iter().filter(|_| rng.gen::<f64>() < p).collect();
+	SimpleNetwork::from_edges((0..n).collect(), edges).expect("each pair is drawn once")
+}
 
-				}
-			}
-			//dbg!(split.elapsed());
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::analysis::Analysis;
 
-		}
-		pairs
+	#[test]
+	fn edits_report_the_node_or_edge_at_fault() {
+		let mut network = SimpleNetwork::from_node_vec(vec![1u64,2,3]);
+		assert!(network.add_edge(1,2).is_ok());
+		assert!(matches!(network.add_edge(2,1), Err(Error::DuplicateEdge{from: 2, to: 1})));
+		assert!(matches!(network.add_edge(3,3), Err(Error::SelfLoop(3))));
+		assert!(matches!(network.add_edge(1,9), Err(Error::UnknownNode(9))));
+		assert!(matches!(network.remove_edge(1,3), Err(Error::MissingEdge{from: 1, to: 3})));
+		assert_eq!(network.num_edges(), 1);
 	}
 	
-	pub fn edgelist(&self) -> HashSet<(N,N)> {
-		let mut pairs = HashSet::new();
-		for src in self.nodes.clone() {
-			if let Some(adjs) = self.adjacencies.get(&src) {
-				for dst in adjs {
-					pairs.insert((src,dst.clone()));
-				}
-			}
-		}
-		pairs
+	#[test]
+	fn removing_a_node_keeps_the_network_valid() -> Result<()> {
+		// the declared degrees are wrong, and 2-3 is only listed from one side
+		let adjs = vec![
+			Adjacency{node_id: 1u64, name: "a".into(), id_again: 1, degree: 5, edges: vec![2,3]},
+			Adjacency{node_id: 2, name: "b".into(), id_again: 2, degree: 0, edges: vec![1,3]},
+			Adjacency{node_id: 3, name: "c".into(), id_again: 3, degree: 1, edges: vec![1]},
+		];
+		let mut network = SimpleNetwork::from_adj_list(adjs)?;
+		network.validate()?;
+		assert_eq!((network.num_edges(),network.degree(3)), (3,2));
+		
+		network.remove_node(1)?;
+		network.validate()?;
+		assert_eq!((network.num_nodes(),network.num_edges()), (2,1));
+		assert!(network.has_edge(3,2) && !network.has_edge(1,2));
+		assert_eq!(network.neighbors(2).collect::<Vec<_>>(), vec![3]);
+		assert!(matches!(network.remove_node(1), Err(Error::UnknownNode(1))));
+		Ok(())
+	}
+	
+	#[test]
+	fn components_are_labeled_by_size() -> Result<()> {
+		// triangle 1-2-3, edge 4-5, isolated 6 and 0
+		let network = SimpleNetwork::from_edges(vec![0u64,1,2,3,4,5,6], [(1,2),(2,3),(3,1),(4,5)])?;
+		let components = network.connected_components();
+		assert_eq!(components.sizes, vec![3,2,1,1]);
+		assert_eq!(components.members(0), &[1,2,3]);
+		assert_eq!(components.members_by_label()[2..], [vec![0],vec![6]]);
+		assert_eq!(components.labels[&0], 2);
+		assert_eq!(components.labels[&6], 3);
+		assert!((components.giant_fraction() - 3.0/7.0).abs() < 1e-12);
+
+		let small = network.induced_subgraph(components.members(1).iter().copied());
+		assert_eq!((small.num_nodes(),small.num_edges()), (2,1));
+		let partial = network.induced_subgraph([1,2,9]);
+		assert_eq!((partial.num_nodes(),partial.num_edges()), (2,1));
+		Ok(())
 	}
 	
-	//uses node ids, will take a lot of space
-	//assumes that node ids are 0..n-1
-	pub fn apsp(&self) -> Vec<Vec<i64>> {
-		let mut all_paths = vec![vec![-1; self.nodes.len()]; self.nodes.len()];
-		for node in self.nodes.clone(){
-			let paths = self.sssp(node);
-			for (dst,length) in paths {
-				all_paths[node.id() as usize][dst.id() as usize] = length;
+	#[test]
+	fn seeded_swaps_and_rewires_are_reproducible() -> Result<()> {
+		use rand::SeedableRng;
+		use rand_chacha::ChaCha8Rng;
+		
+		// a ring lattice with each node linked to the next two
+		let lattice = || -> Result<SimpleNetwork> {
+			SimpleNetwork::from_edges((0..200u64).collect(), (0..200).flat_map(|i| [(i,(i+1)%200),(i,(i+2)%200)]))
+		};
+		let chain = |rng: &mut ChaCha8Rng| -> Result<SimpleNetwork> {
+			let mut network = lattice()?;
+			for _ in 0..50 {
+				network.double_edge_swap_with_rng(rng)?;
 			}
-		}
-		all_paths
+			network.rewire_edge_with_rng(0, 1, rng)?;
+			network.validate()?;
+			Ok(network)
+		};
+		let first = chain(&mut ChaCha8Rng::seed_from_u64(1))?;
+		let second = chain(&mut ChaCha8Rng::seed_from_u64(1))?;
+		assert_eq!(first.edges().collect::<Vec<_>>(), second.edges().collect::<Vec<_>>());
+		assert!(!first.has_edge(0,1) && first.num_edges() == 400);
+		assert_eq!((first.degree(0),first.degree(1)), (4,3));
+		
+		let mut single = SimpleNetwork::from_edges(vec![1u64,2], [(1,2)])?;
+		assert!(single.double_edge_swap_with_rng(&mut ChaCha8Rng::seed_from_u64(1)).is_err());
+		Ok(())
 	}
 	
-}
\ No newline at end of file
+	#[test]
+	fn impossible_swaps_and_rewires_fail() -> Result<()> {
+		use rand::SeedableRng;
+		use rand_chacha::ChaCha8Rng;
+		
+		let mut rng = ChaCha8Rng::seed_from_u64(1);
+		let mut star = SimpleNetwork::from_edges(vec![0u64,1,2,3], [(0,1),(0,2),(0,3)])?;
+		assert!(matches!(star.double_edge_swap_with_rng(&mut rng), Err(Error::InvalidParameter(_))));
+		assert!(matches!(star.rewire_edge_with_rng(0, 1, &mut rng), Err(Error::InvalidParameter(_))));
+		star.validate()?;
+		assert_eq!(star.num_edges(), 3);
+		
+		// a leaf can still be rewired to another leaf
+		star.rewire_edge_with_rng(1, 0, &mut rng)?;
+		assert!(!star.has_edge(0,1) && star.degree(1) == 1);
+		Ok(())
+	}
+}
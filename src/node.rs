@@ -1,9 +1,11 @@
 //! An abstracted node.
 
 /// Abstracts over a single node in a network.
+///
+/// Nodes are `Send + Sync` so that per-source searches can run in parallel.
 /// TODO: change to `Vertex` to avoid ambiguity with possible `Network` trait
 pub trait Node:
-    Default + std::fmt::Debug + std::fmt::Display + PartialEq + Eq + Copy + Clone + std::hash::Hash + Ord + PartialOrd
+    Default + std::fmt::Debug + std::fmt::Display + PartialEq + Eq + Copy + Clone + std::hash::Hash + Ord + PartialOrd + Send + Sync
 {
     /// The (unique) id of the node.
     fn id(&self) -> u64;
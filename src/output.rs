@@ -1,7 +1,17 @@
 //! Utilities for outputting data.
 
 use serde::{ser::SerializeSeq, Serialize};
-use std::io::Write;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{create_dir_all, File};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::{Error, Result};
+use crate::graph::Graph;
+use crate::interner::Interner;
+use crate::node::Node;
 
 struct OutputCsvRow<'a> {
     name: &'a str,
@@ -39,7 +49,7 @@ pub fn to_csv<W: Write>(
     floats: &[f64],
     ints: &[u64],
     writer: W,
-) -> Result<(), csv::Error> {
+) -> Result<()> {
     let output = OutputCsvRow {
         name,
         floats,
@@ -48,5 +58,367 @@ pub fn to_csv<W: Write>(
 
     let mut wtr = csv::Writer::from_writer(writer);
 
-    wtr.serialize(output)
+    Ok(wtr.serialize(output)?)
+}
+
+/// A network in node-link JSON form, with analysis results attached.
+///
+/// The layout is the one d3 uses and `networkx.node_link_graph` reads:
+///
+/// ```text
+/// {"directed": false, "multigraph": false, "graph": {...run metadata...},
+///  "nodes": [{"id": 1, "harmonic": 0.4, ...}, ...],
+///  "links": [{"source": 1, "target": 2}, ...]}
+/// ```
+///
+/// Per-node results (centralities, partition labels, attributes) become node
+/// keys, and anything about the run as a whole (e.g. `fitDCSBM` likelihood
+/// traces) goes under `graph`.
+#[derive(Debug, Clone)]
+pub struct NodeLinkGraph<N: Node> {
+    graph: Map<String, Value>,
+    nodes: Vec<(N, Map<String, Value>)>,
+    links: Vec<(N, N)>,
+}
+
+impl<N: Node> NodeLinkGraph<N> {
+    pub fn from_network<G: Graph<Node = N>>(network: &G) -> Self {
+        let mut nodes: Vec<N> = network.nodes().collect();
+        nodes.sort();
+        let mut links: Vec<(N, N)> = network.edges().collect();
+        links.sort();
+        Self {
+            graph: Map::new(),
+            nodes: nodes.into_iter().map(|n| (n, Map::new())).collect(),
+            links,
+        }
+    }
+
+    /// Attaches a per-node value under `name`. Nodes missing from `values` get no key.
+    pub fn with_node_attribute<V: Serialize>(mut self, name: &str, values: &HashMap<N, V>) -> serde_json::Result<Self> {
+        for (node, attrs) in &mut self.nodes {
+            if let Some(value) = values.get(node) {
+                attrs.insert(name.to_owned(), serde_json::to_value(value)?);
+            }
+        }
+        Ok(self)
+    }
+
+    /// Attaches group labels from a partition (as used by `fitDCSBM`) under `name`.
+    pub fn with_partition(self, name: &str, partition: &HashMap<N, (usize, bool)>) -> serde_json::Result<Self> {
+        let groups: HashMap<N, usize> = partition.iter().map(|(node, (grp, _))| (*node, *grp)).collect();
+        self.with_node_attribute(name, &groups)
+    }
+
+    /// Attaches each node's string label under `"label"`.
+    pub fn with_labels(mut self, interner: &Interner) -> Self {
+        for (node, attrs) in &mut self.nodes {
+            if let Some(label) = interner.label(node.id()) {
+                attrs.insert("label".to_owned(), Value::from(label));
+            }
+        }
+        self
+    }
+
+    /// Attaches a value about the whole run (parameters, likelihood traces, ...).
+    pub fn with_graph_attribute<V: Serialize>(mut self, name: &str, value: &V) -> serde_json::Result<Self> {
+        self.graph.insert(name.to_owned(), serde_json::to_value(value)?);
+        Ok(self)
+    }
+
+    pub fn to_json(&self) -> Value {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|(node, attrs)| {
+                let mut obj = attrs.clone();
+                obj.insert("id".to_owned(), Value::from(node.id()));
+                Value::Object(obj)
+            })
+            .collect::<Vec<_>>();
+        let links = self
+            .links
+            .iter()
+            .map(|(u, v)| json!({"source": u.id(), "target": v.id()}))
+            .collect::<Vec<_>>();
+        json!({
+            "directed": false,
+            "multigraph": false,
+            "graph": self.graph,
+            "nodes": nodes,
+            "links": links,
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: W) -> serde_json::Result<()> {
+        serde_json::to_writer(writer, &self.to_json())
+    }
+}
+
+/// Identifies one invocation, so that result rows can be traced back to it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunMetadata {
+    pub run_id: String,
+    /// The RNG seed, or `None` if the run was not seeded.
+    pub seed: Option<u64>,
+    /// Start of the run, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub parameters: BTreeMap<String, String>,
+}
+
+impl RunMetadata {
+    /// Starts a new run, with an id built from the start time and process id.
+    pub fn new(seed: Option<u64>) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self {
+            run_id: format!("{:x}-{}", timestamp, std::process::id()),
+            seed,
+            timestamp,
+            parameters: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_parameter<V: std::fmt::Display>(mut self, name: &str, value: V) -> Self {
+        self.parameters.insert(name.to_owned(), value.to_string());
+        self
+    }
+
+    /// Appends the metadata as one JSON line to `<dir>/runs.jsonl`.
+    pub fn record<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
+        create_dir_all(&dir)?;
+        let mut runs = File::options()
+            .append(true)
+            .create(true)
+            .open(dir.as_ref().join("runs.jsonl"))?;
+        writeln!(runs, "{}", serde_json::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// A single typed value in a [`ResultWriter`] row.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Field {
+    Text(String),
+    Float(f64),
+    Int(i64),
+}
+
+impl From<&str> for Field {
+    fn from(value: &str) -> Self {
+        Field::Text(value.to_owned())
+    }
+}
+
+impl From<String> for Field {
+    fn from(value: String) -> Self {
+        Field::Text(value)
+    }
+}
+
+impl From<f64> for Field {
+    fn from(value: f64) -> Self {
+        Field::Float(value)
+    }
+}
+
+impl From<i64> for Field {
+    fn from(value: i64) -> Self {
+        Field::Int(value)
+    }
+}
+
+impl From<u64> for Field {
+    fn from(value: u64) -> Self {
+        Field::Int(value as i64)
+    }
+}
+
+impl From<usize> for Field {
+    fn from(value: usize) -> Self {
+        Field::Int(value as i64)
+    }
+}
+
+/// An analysis result that is written as one [`ResultWriter`] row per item,
+/// e.g. one degree class of a spectrum.
+pub trait Row {
+    /// Column names, after the leading `name` column
+    const COLUMNS: &'static [&'static str];
+
+    fn fields(&self) -> Vec<Field>;
+
+    /// The full header: `name` followed by [`COLUMNS`](Self::COLUMNS)
+    fn header() -> Vec<&'static str> {
+        let mut header = vec!["name"];
+        header.extend_from_slice(Self::COLUMNS);
+        header
+    }
+}
+
+/// Writes result rows under a header, tagged with the run that produced them.
+///
+/// Rows are appended to `<dir>/<file>`, with the run id as an extra last
+/// column (so positional readers see the same columns as before). The header
+/// is written when the file is new, and an existing file with a different
+/// header is refused rather than mixed. The rest of the run metadata lives in
+/// `<dir>/runs.jsonl`, see [`RunMetadata::record`].
+///
+/// Variable-length rows (likelihood traces, per-node draws) don't fit a fixed
+/// header and still go through [`to_csv`].
+pub struct ResultWriter {
+    writer: csv::Writer<File>,
+    columns: usize,
+    run_id: String,
+}
+
+impl ResultWriter {
+    pub fn open<P: AsRef<Path>>(dir: P, file: &str, columns: &[&str], run: &RunMetadata) -> Result<Self> {
+        let dir = dir.as_ref();
+        create_dir_all(dir)?;
+        let path = dir.join(file);
+
+        let mut header: Vec<&str> = columns.to_vec();
+        header.push("run_id");
+        let header_line = header.join(",");
+
+        let existing = match File::open(&path) {
+            Ok(f) => BufReader::new(f).lines().next().transpose()?,
+            Err(_) => None,
+        };
+        let is_new = match existing {
+            Some(line) if line.trim_end() != header_line => {
+                return Err(Error::InvalidData(format!(
+                    "{} has header `{}`, expected `{}`",
+                    path.display(),
+                    line.trim_end(),
+                    header_line
+                )));
+            }
+            Some(_) => false,
+            None => true,
+        };
+
+        let mut writer = csv::WriterBuilder::new()
+            .has_headers(false)
+            .flexible(false)
+            .from_writer(File::options().append(true).create(true).open(&path)?);
+        if is_new {
+            writer.write_record(&header)?;
+            writer.flush()?;
+        }
+
+        Ok(Self {
+            writer,
+            columns: columns.len(),
+            run_id: run.run_id.clone(),
+        })
+    }
+
+    /// Appends one row; it must have one field per column.
+    pub fn write_row(&mut self, row: &[Field]) -> Result<()> {
+        if row.len() != self.columns {
+            return Err(Error::InvalidParameter(format!(
+                "expected {} fields, got {}",
+                self.columns,
+                row.len()
+            )));
+        }
+        let mut record: Vec<String> = row
+            .iter()
+            .map(|field| match field {
+                Field::Text(s) => s.clone(),
+                Field::Float(f) => f.to_string(),
+                Field::Int(i) => i.to_string(),
+            })
+            .collect();
+        record.push(self.run_id.clone());
+        self.writer.write_record(&record)?;
+        // flushed per row so that an interrupted run keeps what it finished
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Appends one row per item, each starting with the dataset `name`. The
+    /// writer should have been opened with [`Row::header`].
+    pub fn write_rows<R: Row, I: IntoIterator<Item = R>>(&mut self, name: &str, rows: I) -> Result<()> {
+        for row in rows {
+            let mut fields = vec![name.into()];
+            fields.extend(row.fields());
+            self.write_row(&fields)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimpleNetwork;
+
+    #[test]
+    fn node_link_graph_round_trip() -> crate::Result<()> {
+        let network = SimpleNetwork::from_edges(vec![7u64, 3, 5, 1], [(7, 3), (5, 1), (1, 7)])?;
+        let harmonic = HashMap::from([(3u64, 0.5), (7, 1.0)]);
+        let partition = HashMap::from([(1u64, (0, false)), (3, (1, true)), (5, (0, false)), (7, (1, false))]);
+        // ids 0..4, so only nodes 1 and 3 have a label
+        let mut interner = Interner::new();
+        for label in ["w", "x", "y", "z"] {
+            interner.intern(label);
+        }
+        let graph = NodeLinkGraph::from_network(&network)
+            .with_node_attribute("harmonic", &harmonic)?
+            .with_partition("group", &partition)?
+            .with_labels(&interner)
+            .with_graph_attribute("seed", &42)?;
+        let mut bytes = vec![];
+        graph.write(&mut bytes)?;
+
+        let json: Value = serde_json::from_slice(&bytes)?;
+        assert_eq!(json["graph"]["seed"], 42);
+        let ids: Vec<u64> = json["nodes"].as_array().unwrap().iter().map(|n| n["id"].as_u64().unwrap()).collect();
+        assert_eq!(ids, vec![1, 3, 5, 7]);
+        assert_eq!(json["nodes"][1]["harmonic"], 0.5);
+        assert!(json["nodes"][0].get("harmonic").is_none());
+        let groups: Vec<u64> = json["nodes"].as_array().unwrap().iter().map(|n| n["group"].as_u64().unwrap()).collect();
+        assert_eq!(groups, vec![0, 1, 0, 1]);
+        assert_eq!((&json["nodes"][0]["label"], &json["nodes"][1]["label"]), (&Value::from("x"), &Value::from("z")));
+        assert!(json["nodes"][2].get("label").is_none());
+        // each edge once, smaller end first, sorted
+        let links: Vec<(u64, u64)> = json["links"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|l| (l["source"].as_u64().unwrap(), l["target"].as_u64().unwrap()))
+            .collect();
+        assert_eq!(links, vec![(1, 5), (1, 7), (3, 7)]);
+        Ok(())
+    }
+
+    #[test]
+    fn result_rows_carry_the_run_id() -> crate::Result<()> {
+        let dir = std::env::temp_dir().join(format!("hw5352_output_{}", std::process::id()));
+        let run = RunMetadata::new(Some(7)).with_parameter("reps", 3);
+        run.record(&dir)?;
+        let mut writer = ResultWriter::open(&dir, "results.csv", &["name", "value"], &run)?;
+        writer.write_row(&["school".into(), 0.5.into()])?;
+        assert!(writer.write_row(&["school".into()]).is_err());
+        // reopening appends under the same header, a different one is refused
+        ResultWriter::open(&dir, "results.csv", &["name", "value"], &run)?.write_row(&["other".into(), 2u64.into()])?;
+        assert!(ResultWriter::open(&dir, "results.csv", &["name"], &run).is_err());
+
+        let csv = std::fs::read_to_string(dir.join("results.csv"))?;
+        let expected = format!("name,value,run_id\nschool,0.5,{0}\nother,2,{0}\n", run.run_id);
+        assert_eq!(csv, expected);
+        let runs = std::fs::read_to_string(dir.join("runs.jsonl"))?;
+        let recorded: Value = serde_json::from_str(runs.trim_end())?;
+        assert_eq!(recorded["run_id"], run.run_id.as_str());
+        assert_eq!(recorded["seed"], 7);
+        assert_eq!(recorded["parameters"]["reps"], "3");
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
 }
@@ -1,20 +1,16 @@
 //TODO: add documentation
-use crate::hw1::{AttrNode,Edge};
-use rand::seq::SliceRandom;
 use rand::Rng;
 use std::collections::HashMap;
-use std::collections::HashSet;
-use crate::SimpleNetwork;
-use std::time::Instant;
-use crate::node::Node;
+use crate::graph::Graph;
 
-pub fn log_likelyhood_DCSBM(mixing_matrix: &Vec<Vec<usize>>, grp_degrees: &Vec<usize>) -> f64 {
+/// Group of each node, and whether it has been moved in the current phase
+pub type Partition<N> = HashMap<N,(usize,bool)>;
+
+#[allow(non_snake_case)]
+pub fn log_likelyhood_DCSBM(mixing_matrix: &[Vec<usize>], grp_degrees: &[usize]) -> f64 {
 	let mut log_likelyhood = 0.0;
-	for r in 0..mixing_matrix.len() {
-		let kr = grp_degrees[r];
-		for s in 0..mixing_matrix.len() {
-			let ks = grp_degrees[s];
-			let wrs = mixing_matrix[r][s];
+	for (row,&kr) in mixing_matrix.iter().zip(grp_degrees) {
+		for (&wrs,&ks) in row.iter().zip(grp_degrees) {
 			if wrs != 0 {
 				log_likelyhood += (wrs as f64)*(((wrs as f64)/(kr as f64)/(ks as f64)).ln());
 			}
@@ -23,15 +19,13 @@ pub fn log_likelyhood_DCSBM(mixing_matrix: &Vec<Vec<usize>>, grp_degrees: &Vec<u
 	log_likelyhood
 }
 
-pub fn compute_mixing_matrix<N:Node>(G: &SimpleNetwork<N>, c: usize, partition: &HashMap<N,(usize,bool)>) -> Vec<Vec<usize>> {
+pub fn compute_mixing_matrix<G: Graph>(graph: &G, c: usize, partition: &Partition<G::Node>) -> Vec<Vec<usize>> {
 	let mut matrix = vec![vec![0; c]; c];
-	for node in &G.nodes {
+	for node in graph.nodes() {
 		if let Some((grp,_)) = partition.get(&node){
-			if let Some(adjs) = G.get_adjs(*node){
-				for neighbor in adjs {
-					if let Some((grp2,_)) = partition.get(neighbor){
-						matrix[*grp][*grp2] += 1;
-					}
+			for neighbor in graph.neighbors(node) {
+				if let Some((grp2,_)) = partition.get(&neighbor){
+					matrix[*grp][*grp2] += 1;
 				}
 			}
 		}
@@ -40,18 +34,23 @@ pub fn compute_mixing_matrix<N:Node>(G: &SimpleNetwork<N>, c: usize, partition:
 }
 
 //can make this faster if we don't actually move each node
-pub fn makeAMove<N:Node>(G: &SimpleNetwork<N>, partition: &HashMap<N,(usize,bool)>, num_groups: usize) -> (f64,(N,usize)) {
+#[allow(non_snake_case)]
+pub fn makeAMove<G: Graph>(graph: &G, partition: &Partition<G::Node>, num_groups: usize) -> (f64,(G::Node,usize)) {
 	let mut max_likelyhood = 0.0;
-	let mut best_move = (partition.clone().into_keys().collect::<Vec<N>>()[0],0);
+	//nodes in sorted order, so ties go the same way in every run
+	let mut nodes: Vec<G::Node> = partition.keys().copied().collect();
+	nodes.sort();
+	let mut best_move = (*nodes.first().expect("partition has no nodes"),0);
 	let mut test_partition = partition.clone();
-	for (node,(grp,frozen)) in partition {
+	for node in &nodes {
+		let (grp,frozen) = &partition[node];
 		if !frozen {
 			//compute changes to mixing matrix by group
 			for dst in 0..num_groups {
 				if dst != *grp {
 					//calculate log-likelyhood of moving node i to grp
 					test_partition.insert(*node, (dst,false));
-					let mixing_matrix = compute_mixing_matrix(G, num_groups, &test_partition);
+					let mixing_matrix = compute_mixing_matrix(graph, num_groups, &test_partition);
 					let mut grp_degrees = vec![];
 					for grp in &mixing_matrix {
 						grp_degrees.push(grp.iter().sum());
@@ -70,38 +69,38 @@ pub fn makeAMove<N:Node>(G: &SimpleNetwork<N>, partition: &HashMap<N,(usize,bool
 	(max_likelyhood, best_move)
 }
 
-pub fn runOnePhase<N:Node>(G: &SimpleNetwork<N>, mut partition: HashMap<N,(usize,bool)>, num_groups: usize) -> (HashMap<N,(usize,bool)>, f64, bool, Vec<f64>){
-	let mut mixing_matrix = compute_mixing_matrix(G, num_groups, &partition);
+#[allow(non_snake_case)]
+pub fn runOnePhase<G: Graph>(graph: &G, mut partition: Partition<G::Node>, num_groups: usize) -> (Partition<G::Node>, f64, bool, Vec<f64>){
+	let mut mixing_matrix = compute_mixing_matrix(graph, num_groups, &partition);
 	let mut grp_degrees = vec![];
 	for grp in &mixing_matrix {
 		grp_degrees.push(grp.iter().sum());
 	}
 	
 	let mut likelyhoods = vec![];
-	let mut z0_likelyhood = log_likelyhood_DCSBM(&mixing_matrix,&grp_degrees);
+	let z0_likelyhood = log_likelyhood_DCSBM(&mixing_matrix,&grp_degrees);
 	let mut halt = true;
 	let mut best_partition = partition.clone();
 	let mut max_likelyhood = z0_likelyhood;
 	likelyhoods.push(z0_likelyhood);
 	
-	for t in 0..G.nodes.len() {
-		let (next_likelyhood,(node,dst)) = makeAMove(G,&partition, num_groups);
-		partition.insert(node, (dst,true));
-		if let Some(neighborhood) = G.get_adjs(node) {
-			let k_node = neighborhood.len();
-			if let Some((grp,_)) = partition.get(&node) {
-				grp_degrees[*grp] -= k_node;
-				grp_degrees[dst] += k_node;
-				for neighbor in neighborhood {
-					if let Some((neighbor_grp,_)) = partition.get(&neighbor) {
-						mixing_matrix[*grp][*neighbor_grp] -= 1;
-						mixing_matrix[*neighbor_grp][*grp] -= 1;
-						mixing_matrix[dst][*neighbor_grp] += 1;
-						mixing_matrix[*neighbor_grp][dst] += 1;
-					}
+	for _ in 0..graph.num_nodes() {
+		let (next_likelyhood,(node,dst)) = makeAMove(graph,&partition, num_groups);
+		let k_node = graph.degree(node);
+		//move the node's edges out of its old group before it is reassigned
+		if let Some(&(grp,_)) = partition.get(&node) {
+			grp_degrees[grp] -= k_node;
+			grp_degrees[dst] += k_node;
+			for neighbor in graph.neighbors(node) {
+				if let Some((neighbor_grp,_)) = partition.get(&neighbor) {
+					mixing_matrix[grp][*neighbor_grp] -= 1;
+					mixing_matrix[*neighbor_grp][grp] -= 1;
+					mixing_matrix[dst][*neighbor_grp] += 1;
+					mixing_matrix[*neighbor_grp][dst] += 1;
 				}
 			}
 		}
+		partition.insert(node, (dst,true));
 		likelyhoods.push(next_likelyhood);
 		if next_likelyhood > max_likelyhood {
 			halt = false;
@@ -113,23 +112,23 @@ pub fn runOnePhase<N:Node>(G: &SimpleNetwork<N>, mut partition: HashMap<N,(usize
 	(best_partition, max_likelyhood, halt, likelyhoods)
 }
 
-pub fn fitDCSBM<N:Node>(G: SimpleNetwork<N>, c: usize, T: usize) -> (HashMap<N,(usize,bool)>, f64, Vec<f64>){
+#[allow(non_snake_case)]
+pub fn fitDCSBM<G: Graph, R: Rng + ?Sized>(graph: &G, c: usize, phases: usize, rng: &mut R) -> (Partition<G::Node>, f64, Vec<f64>){
 	//pick random initial partition z0
-	let mut rng = rand::thread_rng();
 	let mut partition = HashMap::new();
-	for node in &G.nodes {
-		partition.insert(*node, (rng.gen_range(0..c), false));
+	for node in graph.nodes() {
+		partition.insert(node, (rng.gen_range(0..c), false));
 	}
 	let mut likelyhoods = vec![];
 	
-	for p in 0..T {
-		let (new_partition, phase_likelyhood, halt, mut phase_likelyhoods) = runOnePhase(&G, partition.clone(), c);
-		for (node,(grp,frozen)) in &new_partition {
+	for p in 0..phases {
+		let (new_partition, phase_likelyhood, halt, mut phase_likelyhoods) = runOnePhase(graph, partition.clone(), c);
+		for (node,(grp,_)) in &new_partition {
 			partition.insert(*node,(*grp,false));
 		}
 		
 		likelyhoods.append(&mut phase_likelyhoods);
-		if halt || p == T-1 {
+		if halt || p == phases-1 {
 			return (new_partition, phase_likelyhood, likelyhoods);
 		}
 	} 
@@ -138,7 +137,19 @@ pub fn fitDCSBM<N:Node>(G: SimpleNetwork<N>, c: usize, T: usize) -> (HashMap<N,(
 	
 }
 
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::SimpleNetwork;
+	use rand::SeedableRng;
+	use rand_chacha::ChaCha8Rng;
 
-
-
-
+	#[test]
+	fn seeded_fits_agree() -> crate::Result<()> {
+		//a ring is full of moves that tie
+		let network = SimpleNetwork::from_edges((0..20u64).collect(), (0..20).map(|i| (i,(i+1)%20)))?;
+		let fit = |seed: u64| fitDCSBM(&network, 2, 5, &mut ChaCha8Rng::seed_from_u64(seed));
+		assert_eq!(fit(7), fit(7));
+		Ok(())
+	}
+}
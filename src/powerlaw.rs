@@ -0,0 +1,375 @@
+//! Fitting discrete power laws, after Clauset, Shalizi and Newman (2009).
+//!
+//! For a lower cutoff `x_min`, the tail `x >= x_min` is modelled as
+//! `p(x) = x^-alpha / zeta(alpha, x_min)`, with `zeta` the Hurwitz zeta
+//! function, and `alpha` is found by maximizing the likelihood. [`fit`]
+//! tries every observed value as `x_min` and keeps the one whose fit is
+//! closest to the data in the Kolmogorov-Smirnov distance `D`.
+//!
+//! A small `D` does not by itself say the data follow a power law.
+//! [`goodness_of_fit`] asks how often data drawn from the fitted model do
+//! worse, refitting each synthetic data set from scratch, and
+//! [`compare_lognormal`] and [`compare_exponential`] run Vuong's likelihood
+//! ratio test of the tail against those alternatives.
+//!
+//! Values of 0 (isolated nodes, for degrees) are ignored throughout.
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
+
+use crate::error::{Error, Result};
+
+/// A power law fitted to the tail of some data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowerLawFit {
+    pub alpha: f64,
+    pub x_min: usize,
+    /// Number of values `>= x_min`
+    pub tail: usize,
+    /// Kolmogorov-Smirnov distance between the tail and the fit
+    pub ks: f64,
+}
+
+/// Outcome of a likelihood ratio test of the power law against an
+/// alternative.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LikelihoodRatio {
+    /// Log likelihood of the power law minus that of the alternative,
+    /// summed over the tail: positive favours the power law
+    pub ratio: f64,
+    /// Two-sided significance of the sign of `ratio`. A large p means the
+    /// data cannot tell the two apart
+    pub p: f64,
+}
+
+/// Largest exponent considered. Only reached when nearly all of the tail
+/// sits at `x_min`.
+const MAX_ALPHA: f64 = 20.0;
+
+/// Fits a power law to the tail of `data`, choosing `x_min` by minimizing
+/// the KS distance. Every distinct value but the largest is tried.
+///
+/// `None` if there are fewer than two distinct positive values.
+pub fn fit(data: &[usize]) -> Option<PowerLawFit> {
+    let data = positive_sorted(data);
+    let mut candidates = data.clone();
+    candidates.dedup();
+    candidates.pop();
+    candidates.into_iter().map(|x_min| fit_sorted(&data, x_min)).min_by(|a, b| a.ks.total_cmp(&b.ks))
+}
+
+/// Fits the exponent of a power law to the values of `data` at least
+/// `x_min`, which must be positive.
+///
+/// `None` if fewer than two distinct values are at least `x_min`.
+pub fn fit_x_min(data: &[usize], x_min: usize) -> Result<Option<PowerLawFit>> {
+    if x_min == 0 {
+        return Err(Error::InvalidParameter("x_min must be positive".to_owned()));
+    }
+    let data = positive_sorted(data);
+    let tail = &data[data.partition_point(|&x| x < x_min)..];
+    Ok((tail.first() != tail.last()).then(|| fit_sorted(&data, x_min)))
+}
+
+fn positive_sorted(data: &[usize]) -> Vec<usize> {
+    let mut data: Vec<usize> = data.iter().copied().filter(|&x| x > 0).collect();
+    data.sort_unstable();
+    data
+}
+
+/// The fit for `x_min`, given sorted positive data with at least two
+/// distinct values from `x_min` on
+fn fit_sorted(data: &[usize], x_min: usize) -> PowerLawFit {
+    let tail = &data[data.partition_point(|&x| x < x_min)..];
+    let n = tail.len() as f64;
+    let log_sum: f64 = tail.iter().map(|&x| (x as f64).ln()).sum();
+    // the log likelihood is concave in alpha, so a golden section search
+    // finds its maximum
+    let log_likelihood = |alpha: f64| -alpha * log_sum - n * hurwitz_zeta(alpha, x_min as f64).ln();
+    let alpha = golden_section_max(log_likelihood, 1.0 + 1e-6, MAX_ALPHA, 1e-7);
+
+    // KS distance: the empirical CDF steps at the data and is flat between
+    // them, while the model's rises at every integer, so it is compared at
+    // both ends of each flat stretch
+    let norm = hurwitz_zeta(alpha, x_min as f64);
+    let (mut ks, mut empirical) = (0.0f64, 0.0);
+    let mut i = 0;
+    while i < tail.len() {
+        let x = tail[i];
+        let below = 1.0 - hurwitz_zeta(alpha, x as f64) / norm;
+        ks = ks.max((below - empirical).abs());
+        i += tail[i..].partition_point(|&y| y == x);
+        empirical = i as f64 / n;
+        ks = ks.max((below + (x as f64).powf(-alpha) / norm - empirical).abs());
+    }
+    PowerLawFit { alpha, x_min, tail: tail.len(), ks }
+}
+
+impl PowerLawFit {
+    /// `P(X >= x)` under the fit, for `x >= x_min`
+    pub fn ccdf(&self, x: usize) -> f64 {
+        hurwitz_zeta(self.alpha, x as f64) / hurwitz_zeta(self.alpha, self.x_min as f64)
+    }
+
+    /// Log probability of each tail value of `data` under the fit
+    fn log_probabilities(&self, data: &[usize]) -> Vec<f64> {
+        let log_norm = hurwitz_zeta(self.alpha, self.x_min as f64).ln();
+        tail(data, self.x_min).map(|x| -self.alpha * x.ln() - log_norm).collect()
+    }
+
+    /// One draw from the fit, by the continuous approximation of Clauset et
+    /// al. (their eq. D.6), which is accurate to a few percent in the CCDF
+    fn sample<R: Rng>(&self, rng: &mut R) -> usize {
+        let r: f64 = rng.gen();
+        ((self.x_min as f64 - 0.5) * (1.0 - r).powf(-1.0 / (self.alpha - 1.0)) + 0.5).floor() as usize
+    }
+}
+
+fn tail(data: &[usize], x_min: usize) -> impl Iterator<Item = f64> + '_ {
+    data.iter().filter(move |&&x| x >= x_min).map(|&x| x as f64)
+}
+
+/// Goodness of fit by parametric bootstrap: the fraction of `reps` synthetic
+/// data sets whose own best fit has a KS distance at least that of `fit` to
+/// `data`.
+///
+/// Each synthetic set has as many values as `data`. Each value is drawn from
+/// the fitted power law with probability `tail / n`, and otherwise resampled
+/// from the data below `x_min`. Clauset et al. rule out the power law when
+/// p <= 0.1; for p accurate to about 0.01 they suggest 2500 repetitions. The
+/// repetitions run in parallel, each seeded from `rng`.
+///
+/// Fails with [`Error::InvalidParameter`] if `reps` is 0 or `data` has no
+/// positive values, as there is then no p to estimate.
+pub fn goodness_of_fit<R: Rng>(data: &[usize], fit: &PowerLawFit, reps: usize, rng: &mut R) -> Result<f64> {
+    if reps == 0 {
+        return Err(Error::InvalidParameter("a goodness of fit test needs at least one repetition".to_owned()));
+    }
+    let data = positive_sorted(data);
+    if data.is_empty() {
+        return Err(Error::InvalidParameter("a goodness of fit test needs positive data".to_owned()));
+    }
+    let body = &data[..data.partition_point(|&x| x < fit.x_min)];
+    let tail_fraction = fit.tail as f64 / data.len() as f64;
+    let seeds: Vec<u64> = (0..reps).map(|_| rng.gen()).collect();
+    let worse = seeds
+        .into_par_iter()
+        .filter(|&seed| {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            let synthetic: Vec<usize> = (0..data.len())
+                .map(|_| {
+                    if body.is_empty() || rng.gen::<f64>() < tail_fraction {
+                        fit.sample(&mut rng)
+                    } else {
+                        body[rng.gen_range(0..body.len())]
+                    }
+                })
+                .collect();
+            self::fit(&synthetic).is_none_or(|refit| refit.ks >= fit.ks)
+        })
+        .count();
+    Ok(worse as f64 / reps as f64)
+}
+
+/// Vuong's test of the power law `fit` against a discrete lognormal fitted
+/// by maximum likelihood to the same tail of `data`.
+///
+/// The lognormal gives each integer `x` the mass of `(x - 1/2, x + 1/2]`
+/// under the continuous distribution, conditioned on `x >= x_min`.
+pub fn compare_lognormal(data: &[usize], fit: &PowerLawFit) -> LikelihoodRatio {
+    let values: Vec<f64> = tail(data, fit.x_min).collect();
+    let cutoff = fit.x_min as f64 - 0.5;
+    let log_probabilities = |mu: f64, sigma: f64| -> Vec<f64> {
+        let log_norm = lognormal_mass(cutoff, f64::INFINITY, mu, sigma).ln();
+        values.iter().map(|&x| lognormal_mass(x - 0.5, x + 0.5, mu, sigma).ln() - log_norm).collect::<Vec<f64>>()
+    };
+    // start from the moments of ln x, and search over ln sigma to keep it
+    // positive
+    let n = values.len() as f64;
+    let mean = values.iter().map(|x| x.ln()).sum::<f64>() / n;
+    let sd = (values.iter().map(|x| (x.ln() - mean).powi(2)).sum::<f64>() / n).sqrt().max(0.1);
+    let [mu, log_sigma] = nelder_mead(|[mu, log_sigma]| -log_probabilities(mu, log_sigma.exp()).iter().sum::<f64>(), [mean, sd.ln()]);
+    vuong(&fit.log_probabilities(data), &log_probabilities(mu, log_sigma.exp()))
+}
+
+/// Vuong's test of the power law `fit` against a discrete exponential
+/// `p(x) = (1 - e^-lambda) e^(-lambda (x - x_min))` fitted by maximum
+/// likelihood to the same tail of `data`.
+pub fn compare_exponential(data: &[usize], fit: &PowerLawFit) -> LikelihoodRatio {
+    let values: Vec<f64> = tail(data, fit.x_min).collect();
+    let x_min = fit.x_min as f64;
+    let excess = values.iter().map(|x| x - x_min).sum::<f64>() / values.len() as f64;
+    let lambda = (1.0 + 1.0 / excess).ln();
+    let log_norm = (-(-lambda).exp_m1()).ln();
+    let exponential: Vec<f64> = values.iter().map(|x| log_norm - lambda * (x - x_min)).collect();
+    vuong(&fit.log_probabilities(data), &exponential)
+}
+
+/// Vuong's normalized likelihood ratio test on the pointwise log
+/// likelihoods of two models
+fn vuong(first: &[f64], second: &[f64]) -> LikelihoodRatio {
+    let differences: Vec<f64> = first.iter().zip(second).map(|(a, b)| a - b).collect();
+    let n = differences.len() as f64;
+    let ratio: f64 = differences.iter().sum();
+    let mean = ratio / n;
+    let variance = differences.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / n;
+    LikelihoodRatio { ratio, p: erfc(ratio.abs() / (2.0 * n * variance).sqrt()) }
+}
+
+/// Mass of the lognormal in `(a, b]`, computed from whichever tail keeps
+/// the difference from cancelling
+fn lognormal_mass(a: f64, b: f64, mu: f64, sigma: f64) -> f64 {
+    let z = |x: f64| (x.ln() - mu) / (sigma * std::f64::consts::SQRT_2);
+    let (za, zb) = (z(a), z(b));
+    if za > 0.0 {
+        0.5 * (erfc(za) - erfc(zb))
+    } else {
+        0.5 * (erfc(-zb) - erfc(-za))
+    }
+}
+
+/// The Hurwitz zeta function `sum_{k >= 0} (q + k)^-s`, for `s > 1` and
+/// `q > 0`, by Euler-Maclaurin summation from the tenth term on
+fn hurwitz_zeta(s: f64, q: f64) -> f64 {
+    const TERMS: usize = 9;
+    // B_2j / (2j)!
+    const BERNOULLI: [f64; 6] = [
+        1.0 / 12.0,
+        -1.0 / 720.0,
+        1.0 / 30240.0,
+        -1.0 / 1209600.0,
+        1.0 / 47900160.0,
+        -691.0 / 1307674368000.0,
+    ];
+    let mut sum: f64 = (0..TERMS).map(|k| (q + k as f64).powf(-s)).sum();
+    let a = q + TERMS as f64;
+    sum += a.powf(1.0 - s) / (s - 1.0) + 0.5 * a.powf(-s);
+    // s (s + 1) ... (s + 2j - 2) a^(-s - 2j + 1)
+    let mut derivative = s * a.powf(-s - 1.0);
+    for (j, b) in BERNOULLI.iter().enumerate() {
+        sum += b * derivative;
+        let j = j as f64;
+        derivative *= (s + 2.0 * j + 1.0) * (s + 2.0 * j + 2.0) / (a * a);
+    }
+    sum
+}
+
+/// The complementary error function, with fractional error below 1.2e-7
+/// everywhere (Numerical Recipes' Chebyshev fit)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807 + t * (-1.13520398 + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let value = t * poly.exp();
+    if x >= 0.0 {
+        value
+    } else {
+        2.0 - value
+    }
+}
+
+/// Maximum of a unimodal `f` on `[lower, upper]`
+fn golden_section_max<F: Fn(f64) -> f64>(f: F, mut lower: f64, mut upper: f64, tolerance: f64) -> f64 {
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let mut a = upper - ratio * (upper - lower);
+    let mut b = lower + ratio * (upper - lower);
+    let (mut fa, mut fb) = (f(a), f(b));
+    while upper - lower > tolerance {
+        if fa < fb {
+            lower = a;
+            a = b;
+            fa = fb;
+            b = lower + ratio * (upper - lower);
+            fb = f(b);
+        } else {
+            upper = b;
+            b = a;
+            fb = fa;
+            a = upper - ratio * (upper - lower);
+            fa = f(a);
+        }
+    }
+    (lower + upper) / 2.0
+}
+
+/// Minimum of `f` over the plane by the Nelder-Mead simplex method, from
+/// `start`
+fn nelder_mead<F: Fn([f64; 2]) -> f64>(f: F, start: [f64; 2]) -> [f64; 2] {
+    let mut simplex: Vec<([f64; 2], f64)> = [start, [start[0] + 0.5, start[1]], [start[0], start[1] + 0.5]]
+        .into_iter()
+        .map(|point| (point, f(point)))
+        .collect();
+    let along = |from: [f64; 2], to: [f64; 2], t: f64| [from[0] + t * (to[0] - from[0]), from[1] + t * (to[1] - from[1])];
+    for _ in 0..1000 {
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+        if (simplex[2].1 - simplex[0].1).abs() < 1e-10 {
+            break;
+        }
+        let centroid = along(simplex[0].0, simplex[1].0, 0.5);
+        let worst = simplex[2];
+        let reflected = along(worst.0, centroid, 2.0);
+        let fr = f(reflected);
+        if fr < simplex[0].1 {
+            let expanded = along(worst.0, centroid, 3.0);
+            let fe = f(expanded);
+            simplex[2] = if fe < fr { (expanded, fe) } else { (reflected, fr) };
+        } else if fr < simplex[1].1 {
+            simplex[2] = (reflected, fr);
+        } else {
+            let contracted = along(worst.0, centroid, 0.5);
+            let fc = f(contracted);
+            if fc < worst.1 {
+                simplex[2] = (contracted, fc);
+            } else {
+                let best = simplex[0].0;
+                for vertex in &mut simplex[1..] {
+                    let point = along(best, vertex.0, 0.5);
+                    *vertex = (point, f(point));
+                }
+            }
+        }
+    }
+    simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+    simplex[0].0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recovers_the_exponent_of_power_law_data() -> Result<()> {
+        assert!((hurwitz_zeta(2.0, 1.0) - std::f64::consts::PI.powi(2) / 6.0).abs() < 1e-12);
+        assert!((hurwitz_zeta(2.5, 3.0) - (hurwitz_zeta(2.5, 1.0) - 1.0 - 2f64.powf(-2.5))).abs() < 1e-12);
+
+        // a power law tail with alpha = 2.5 above 5, on a body of small values
+        let mut rng = ChaCha8Rng::seed_from_u64(11);
+        let truth = PowerLawFit { alpha: 2.5, x_min: 5, tail: 0, ks: 0.0 };
+        let mut data: Vec<usize> = (0..3000).map(|_| truth.sample(&mut rng)).collect();
+        data.extend((0..1000).map(|_| rng.gen_range(0..5)));
+        let fit = fit(&data).unwrap();
+        assert!((fit.alpha - 2.5).abs() < 0.15, "alpha = {}", fit.alpha);
+        assert!((4..=7).contains(&fit.x_min), "x_min = {}", fit.x_min);
+        assert!(goodness_of_fit(&data, &fit, 20, &mut rng)? > 0.1);
+        assert!(matches!(goodness_of_fit(&data, &fit, 0, &mut rng), Err(Error::InvalidParameter(_))));
+        assert!(matches!(goodness_of_fit(&[0, 0], &fit, 20, &mut rng), Err(Error::InvalidParameter(_))));
+        assert!(compare_exponential(&data, &fit).ratio > 0.0);
+        assert!(compare_exponential(&data, &fit).p < 0.01);
+        // a lognormal can mimic a power law, so only check the test is sane
+        let lognormal = compare_lognormal(&data, &fit);
+        assert!(lognormal.ratio.is_finite() && (0.0..=1.0).contains(&lognormal.p));
+
+        // geometric data are not a power law
+        let geometric: Vec<usize> = (0..2000).map(|_| 1 + (rng.gen::<f64>().ln() / 0.8f64.ln()) as usize).collect();
+        let fit = fit_x_min(&geometric, 1)?.unwrap();
+        assert!(compare_exponential(&geometric, &fit).ratio < 0.0);
+        assert!(matches!(fit_x_min(&geometric, 0), Err(Error::InvalidParameter(_))));
+        Ok(())
+    }
+}
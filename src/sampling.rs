@@ -0,0 +1,255 @@
+//! Sampling estimators for distance statistics and centralities.
+//!
+//! The exact versions in [`Analysis`] search from every node. These
+//! search from a sample of sources instead, trading accuracy (with a stated
+//! error) for speed on exploratory runs.
+
+use std::collections::HashMap;
+
+use rand::seq::index::sample;
+use rand::Rng;
+
+use crate::analysis::Analysis;
+use crate::bfs::{BfsWorkspace, UNREACHED};
+use crate::csr::CsrGraph;
+use crate::graph::Graph;
+use crate::node::Node;
+
+/// A sample mean with its standard error and 95% confidence interval.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub value: f64,
+    pub std_error: f64,
+    pub lower: f64,
+    pub upper: f64,
+    pub samples: usize,
+}
+
+impl Estimate {
+    /// Normal-approximation interval for the mean of `values`.
+    ///
+    /// When the sample is the whole population (`population == values.len()`)
+    /// the mean is exact and the error is zero; otherwise the finite
+    /// population correction is applied.
+    pub fn from_sample(values: &[f64], population: usize) -> Self {
+        let k = values.len();
+        let value = values.iter().sum::<f64>() / k as f64;
+        let std_error = if k < 2 || k >= population {
+            0.0
+        } else {
+            let variance = values.iter().map(|v| (v - value).powi(2)).sum::<f64>() / (k - 1) as f64;
+            let correction = ((population - k) as f64 / (population - 1) as f64).sqrt();
+            (variance / k as f64).sqrt() * correction
+        };
+        Self {
+            value,
+            std_error,
+            lower: value - 1.96 * std_error,
+            upper: value + 1.96 * std_error,
+            samples: k,
+        }
+    }
+}
+
+/// Sampled versions of the [`Analysis`] statistics, for any [`Graph`].
+pub trait Sampling: Graph {
+    /// Mean geodesic distance of the largest component, estimated from
+    /// searches from `k` of its nodes chosen without replacement.
+    ///
+    /// Each search gives the mean distance from its source to the rest of the
+    /// component, and the estimate is the mean of those (compare
+    /// `mgd_diameter`, which searches from every node).
+    fn mgd_sampled<R: Rng + ?Sized>(&self, k: usize, rng: &mut R) -> Estimate {
+        let graph = CsrGraph::from_graph(self);
+        let component: Vec<usize> = {
+            let components = self.connected_components();
+            components
+                .members(0)
+                .iter()
+                .filter_map(|node| graph.index_of(node))
+                .collect()
+        };
+        let size = component.len();
+        if size < 2 {
+            return Estimate::from_sample(&[0.0], 1);
+        }
+
+        let mut ws = BfsWorkspace::new(graph.num_nodes());
+        let means: Vec<f64> = sample(rng, size, k.clamp(1, size))
+            .into_iter()
+            .map(|i| {
+                ws.run(&graph, component[i]);
+                let total: u64 = ws.reached().iter().map(|&node| ws.distances()[node] as u64).sum();
+                total as f64 / (size - 1) as f64
+            })
+            .collect();
+        Estimate::from_sample(&means, size)
+    }
+
+    /// Harmonic centrality estimated from `k` random sources (Eppstein and
+    /// Wang's sampling scheme).
+    ///
+    /// Each node's sum of `1/d` over the sampled sources is scaled by `n/k`,
+    /// which makes it unbiased, and normalized by `n - 1` as in
+    /// `harmonic_centrality`.
+    fn harmonic_centrality_sampled<R: Rng + ?Sized>(&self, k: usize, rng: &mut R) -> HashMap<Self::Node, f64> {
+        let graph = CsrGraph::from_graph(self);
+        let n = graph.num_nodes();
+        if n < 2 {
+            return graph.nodes().iter().map(|&node| (node, 0.0)).collect();
+        }
+        let k = k.clamp(1, n);
+
+        let mut totals = vec![0.0; n];
+        let mut ws = BfsWorkspace::new(n);
+        for source in sample(rng, n, k) {
+            ws.run(&graph, source);
+            for &node in &ws.reached()[1..] {
+                totals[node] += 1.0 / ws.distances()[node] as f64;
+            }
+        }
+        let scale = n as f64 / k as f64 / (n - 1) as f64;
+        totals
+            .into_iter()
+            .enumerate()
+            .map(|(i, total)| (graph.node(i), total * scale))
+            .collect()
+    }
+
+    /// Normalized betweenness centrality, estimated to within `epsilon` of
+    /// the true value for every node at once, with probability `1 - delta`
+    /// (Riondato and Kornaropoulos 2016).
+    ///
+    /// Samples `r` node pairs, where `r` grows with `log2` of the vertex
+    /// diameter, and one uniformly random shortest path between each pair;
+    /// every interior node of a sampled path gets `1/r`. The value estimated
+    /// is the fraction of ordered pairs' shortest paths through a node, in
+    /// `[0, 1]` (the exact `betweenness_centrality` returns raw counts).
+    fn betweenness_sampled<R: Rng + ?Sized>(&self, epsilon: f64, delta: f64, rng: &mut R) -> HashMap<Self::Node, f64> {
+        let graph = CsrGraph::from_graph(self);
+        let n = graph.num_nodes();
+        let mut result = vec![0.0; n];
+        if n < 3 {
+            return graph.nodes().iter().map(|&node| (node, 0.0)).collect();
+        }
+
+        let mut ws = BfsWorkspace::new(n);
+        let samples = rk_sample_size(vertex_diameter_bound(&graph, &mut ws), epsilon, delta);
+        let mut paths = vec![0.0f64; n];
+        for _ in 0..samples {
+            let source = rng.gen_range(0..n);
+            let mut target = rng.gen_range(0..n - 1);
+            if target >= source {
+                target += 1;
+            }
+
+            // count shortest paths from the source, in BFS order
+            ws.run(&graph, source);
+            if ws.distances()[target] == UNREACHED {
+                continue;
+            }
+            for &node in ws.reached() {
+                paths[node] = 0.0;
+            }
+            paths[source] = 1.0;
+            for &node in ws.reached() {
+                let next = ws.distances()[node] + 1;
+                for &nbr in graph.neighbors(node) {
+                    if ws.distances()[nbr] == next {
+                        paths[nbr] += paths[node];
+                    }
+                }
+            }
+
+            // walk back from the target, choosing each predecessor in
+            // proportion to its share of the shortest paths
+            let mut node = target;
+            while ws.distances()[node] > 1 {
+                let mut pick = rng.gen::<f64>() * paths[node];
+                let prev_dist = ws.distances()[node] - 1;
+                let mut chosen = None;
+                for &nbr in graph.neighbors(node) {
+                    if ws.distances()[nbr] == prev_dist {
+                        chosen = Some(nbr);
+                        pick -= paths[nbr];
+                        if pick < 0.0 {
+                            break;
+                        }
+                    }
+                }
+                // a predecessor always exists past the source's neighbours
+                node = chosen.unwrap_or(source);
+                result[node] += 1.0 / samples as f64;
+            }
+        }
+        result
+            .into_iter()
+            .enumerate()
+            .map(|(i, b)| (graph.node(i), b))
+            .collect()
+    }
+}
+
+impl<G: Graph + ?Sized> Sampling for G {}
+
+/// Sample size for Riondato-Kornaropoulos with the universal constant 0.5.
+fn rk_sample_size(vertex_diameter: u32, epsilon: f64, delta: f64) -> usize {
+    let vd = vertex_diameter.max(3) as f64;
+    let bits = (vd - 2.0).log2().floor() + 1.0;
+    (0.5 / (epsilon * epsilon) * (bits + (1.0 / delta).ln())).ceil() as usize
+}
+
+/// Upper bound on the number of nodes on any shortest path: in each
+/// component, twice the eccentricity of one node, plus one.
+fn vertex_diameter_bound<N: Node>(graph: &CsrGraph<N>, ws: &mut BfsWorkspace) -> u32 {
+    let mut seen = vec![false; graph.num_nodes()];
+    let mut bound = 1;
+    for source in 0..graph.num_nodes() {
+        if seen[source] {
+            continue;
+        }
+        let reached = ws.run(graph, source);
+        let last = *reached.last().unwrap_or(&source);
+        for &node in reached {
+            seen[node] = true;
+        }
+        bound = bound.max(2 * ws.distances()[last] + 1);
+    }
+    bound
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use crate::SimpleNetwork;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn estimates_are_close_on_a_star_and_path() -> crate::Result<()> {
+        // star with 8 leaves, its hub joined to the end of a path 9-10-11
+        let edges = (1..9).map(|leaf| (0, leaf)).chain([(0, 9), (9, 10), (10, 11)]);
+        let network = SimpleNetwork::from_edges((0..12u64).collect(), edges)?;
+        let mut rng = ChaCha8Rng::seed_from_u64(5);
+
+        let (mgd, _, _) = network.mgd_diameter();
+        let all = network.mgd_sampled(100, &mut rng);
+        assert!((all.value - mgd).abs() < 1e-12 && all.std_error == 0.0);
+        let some = network.mgd_sampled(6, &mut rng);
+        assert!(some.std_error > 0.0 && some.lower < some.upper);
+
+        let exact = network.harmonic_centrality();
+        let sampled = network.harmonic_centrality_sampled(12, &mut rng);
+        for (node, h) in &exact {
+            assert!((sampled[node] - h).abs() < 1e-12);
+        }
+
+        // leaves are never interior; the hub is on the paths between leaves
+        // (8*7 ordered pairs) and from leaves to the path (2*8*3), of 132
+        let betweenness = network.betweenness_sampled(0.05, 0.1, &mut rng);
+        assert_eq!(betweenness[&1], 0.0);
+        assert!(betweenness[&0] > betweenness[&9] && betweenness[&9] > betweenness[&10]);
+        assert!((betweenness[&0] - 104.0 / 132.0).abs() < 0.05);
+        Ok(())
+    }
+}
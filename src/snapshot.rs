@@ -0,0 +1,435 @@
+//! Binary snapshots of a network and its node attributes.
+//!
+//! Parsing the larger FB100 schools takes a noticeable part of every run, so a
+//! network can be saved once in this format and reloaded (or memory-mapped)
+//! afterwards. All values are little-endian `u64`/`i64`, so every section is
+//! 8-byte aligned:
+//!
+//! ```text
+//! magic "HW5352SN" | version u32 | reserved u32 | n | entries | columns
+//! node ids        [n]
+//! offsets         [n + 1]
+//! targets         [entries]
+//! per column:     name length | name bytes (zero padded to 8) | values [n]
+//! crc32 of everything above, as a u64
+//! ```
+//!
+//! The layout is the CSR form from [`crate::csr`], with attribute columns
+//! indexed the same way as the nodes.
+
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::ops::Range;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::csr::CsrGraph;
+use crate::error::{Error, Result};
+use crate::hw1::NodeData;
+use crate::SimpleNetwork;
+
+const MAGIC: &[u8; 8] = b"HW5352SN";
+
+/// Bumped whenever the layout changes; older files are rejected, not migrated.
+pub const FORMAT_VERSION: u32 = 1;
+
+const HEADER_LEN: usize = 40;
+
+/// A named per-node integer attribute, indexed like the CSR nodes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeColumn {
+    pub name: String,
+    pub values: Vec<i64>,
+}
+
+/// A network together with its node attributes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub graph: CsrGraph<u64>,
+    pub columns: Vec<AttributeColumn>,
+}
+
+impl Snapshot {
+    pub fn from_network(network: &SimpleNetwork<u64>) -> Self {
+        Self {
+            graph: CsrGraph::from_network(network),
+            columns: vec![],
+        }
+    }
+
+    /// Adds the FB100 attribute columns. Nodes without a record get 0 (missing).
+    pub fn with_node_data(mut self, node_data: &[NodeData]) -> Self {
+        let mut columns: Vec<AttributeColumn> = NodeData::ATTRIBUTES
+            .iter()
+            .map(|name| AttributeColumn {
+                name: name.to_string(),
+                values: vec![0; self.graph.num_nodes()],
+            })
+            .collect();
+        for record in node_data {
+            if let Some(i) = self.graph.index_of(&record.node_id) {
+                for (column, value) in columns.iter_mut().zip(record.attributes()) {
+                    column.values[i] = value as i64;
+                }
+            }
+        }
+        self.columns.extend(columns);
+        self
+    }
+
+    pub fn to_network(&self) -> Result<SimpleNetwork<u64>> {
+        self.graph.to_network()
+    }
+
+    /// Looks up an attribute column by name.
+    pub fn column(&self, name: &str) -> Option<&AttributeColumn> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+
+    /// Serializes the snapshot, checksum included.
+    pub fn write<W: Write>(&self, writer: W) -> Result<()> {
+        let mut writer = ChecksumWriter {
+            inner: writer,
+            hasher: crc32fast::Hasher::new(),
+        };
+        let graph = &self.graph;
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        writer.write_u64(graph.num_nodes() as u64)?;
+        writer.write_u64(graph.targets().len() as u64)?;
+        writer.write_u64(self.columns.len() as u64)?;
+
+        for node in graph.nodes() {
+            writer.write_u64(*node)?;
+        }
+        for offset in graph.offsets() {
+            writer.write_u64(*offset as u64)?;
+        }
+        for target in graph.targets() {
+            writer.write_u64(*target as u64)?;
+        }
+        for column in &self.columns {
+            if column.values.len() != graph.num_nodes() {
+                return Err(Error::InvalidParameter(format!(
+                    "attribute column `{}` has the wrong length",
+                    column.name
+                )));
+            }
+            let name = column.name.as_bytes();
+            writer.write_u64(name.len() as u64)?;
+            writer.write_all(name)?;
+            writer.write_all(&vec![0; padding(name.len())])?;
+            for value in &column.values {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+
+        let checksum = writer.hasher.clone().finalize() as u64;
+        writer.inner.write_all(&checksum.to_le_bytes())?;
+        writer.inner.flush()?;
+        Ok(())
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> Result<Self> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        SnapshotRef::parse(&bytes)?.to_snapshot()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.write(BufWriter::new(File::create(path)?))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        MappedSnapshot::open(path)?.get().to_snapshot()
+    }
+}
+
+/// A validated snapshot borrowed from a byte buffer.
+///
+/// Values are decoded on access, so nothing is copied until needed.
+#[derive(Debug, Clone, Copy)]
+pub struct SnapshotRef<'a> {
+    nodes: &'a [u8],
+    offsets: &'a [u8],
+    targets: &'a [u8],
+    columns: &'a [u8],
+    num_columns: usize,
+}
+
+/// Where the sections of a validated snapshot lie in its bytes.
+#[derive(Debug, Clone)]
+struct Layout {
+    nodes: Range<usize>,
+    offsets: Range<usize>,
+    targets: Range<usize>,
+    columns: Range<usize>,
+    num_columns: usize,
+}
+
+impl Layout {
+    /// Checks the magic, version, section sizes, checksum and adjacency of
+    /// `bytes`.
+    fn parse(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < HEADER_LEN + 8 || &bytes[0..8] != MAGIC {
+            return Err(corrupt("not a network snapshot"));
+        }
+        let version = read_u64(bytes, 8) as u32;
+        if version != FORMAT_VERSION {
+            return Err(Error::InvalidData(format!(
+                "snapshot format version {} is not supported (expected {})",
+                version, FORMAT_VERSION
+            )));
+        }
+
+        let (body, trailer) = bytes.split_at(bytes.len() - 8);
+        let checksum = read_u64(trailer, 0);
+        if crc32fast::hash(body) as u64 != checksum {
+            return Err(corrupt("snapshot checksum mismatch"));
+        }
+
+        let n = read_u64(body, 16) as usize;
+        let entries = read_u64(body, 24) as usize;
+        let num_columns = read_u64(body, 32) as usize;
+
+        let mut pos = HEADER_LEN;
+        let mut section = |len: usize| -> Result<Range<usize>> {
+            let end = len
+                .checked_mul(8)
+                .and_then(|l| l.checked_add(pos))
+                .filter(|&end| end <= body.len())
+                .ok_or_else(|| corrupt("snapshot is truncated"))?;
+            let range = pos..end;
+            pos = end;
+            Ok(range)
+        };
+        let nodes = section(n)?;
+        let offsets = section(n.checked_add(1).ok_or_else(|| corrupt("snapshot is truncated"))?)?;
+        let targets = section(entries)?;
+        let layout = Self {
+            nodes,
+            offsets,
+            targets,
+            columns: pos..body.len(),
+            num_columns,
+        };
+
+        // the same checks as `CsrGraph::from_parts`, so that `neighbors`
+        // can't index out of bounds
+        let snapshot = layout.view(bytes);
+        let offsets: Vec<u64> = snapshot.offsets.chunks_exact(8).map(|c| read_u64(c, 0)).collect();
+        if offsets.first() != Some(&0) || offsets.last() != Some(&(entries as u64)) {
+            return Err(corrupt("offsets do not match node and target counts"));
+        }
+        if !offsets.windows(2).all(|w| w[0] <= w[1])
+            || snapshot.targets.chunks_exact(8).any(|c| read_u64(c, 0) >= n as u64)
+        {
+            return Err(corrupt("malformed adjacency"));
+        }
+        // walk the columns once so that later accesses can't run off the end
+        if snapshot.column_iter().count() != num_columns
+            || snapshot.column_iter().any(|(_, values)| values.len() != n * 8)
+        {
+            return Err(corrupt("snapshot attribute section is malformed"));
+        }
+        Ok(layout)
+    }
+
+    /// The snapshot in `bytes`, which must be the ones this was parsed from.
+    fn view<'a>(&self, bytes: &'a [u8]) -> SnapshotRef<'a> {
+        SnapshotRef {
+            nodes: &bytes[self.nodes.clone()],
+            offsets: &bytes[self.offsets.clone()],
+            targets: &bytes[self.targets.clone()],
+            columns: &bytes[self.columns.clone()],
+            num_columns: self.num_columns,
+        }
+    }
+}
+
+impl<'a> SnapshotRef<'a> {
+    /// Checks the magic, version, section sizes, checksum and adjacency of
+    /// `bytes`.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self> {
+        Ok(Layout::parse(bytes)?.view(bytes))
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len() / 8
+    }
+
+    pub fn node(&self, i: usize) -> u64 {
+        read_u64(self.nodes, i * 8)
+    }
+
+    /// Indices of the neighbors of node `i`.
+    pub fn neighbors(&self, i: usize) -> impl Iterator<Item = usize> + 'a {
+        let start = read_u64(self.offsets, i * 8) as usize;
+        let end = read_u64(self.offsets, (i + 1) * 8) as usize;
+        let targets = self.targets;
+        (start..end).map(move |j| read_u64(targets, j * 8) as usize)
+    }
+
+    /// Attribute column names and their raw values.
+    fn column_iter(&self) -> impl Iterator<Item = (&'a [u8], &'a [u8])> + 'a {
+        let mut rest = self.columns;
+        let n = self.num_nodes();
+        (0..self.num_columns).map_while(move |_| {
+            if rest.len() < 8 {
+                return None;
+            }
+            let name_len = read_u64(rest, 0) as usize;
+            let name_end = 8usize.checked_add(name_len)?;
+            let values_start = name_end.checked_add(padding(name_len))?;
+            let values_end = values_start.checked_add(n.checked_mul(8)?)?;
+            if values_end > rest.len() {
+                return None;
+            }
+            let column = (&rest[8..name_end], &rest[values_start..values_end]);
+            rest = &rest[values_end..];
+            Some(column)
+        })
+    }
+
+    /// Copies the snapshot into owned form, validating the adjacency.
+    pub fn to_snapshot(&self) -> Result<Snapshot> {
+        let decode = |bytes: &[u8]| -> Vec<usize> {
+            bytes.chunks_exact(8).map(|c| read_u64(c, 0) as usize).collect()
+        };
+        let nodes = self.nodes.chunks_exact(8).map(|c| read_u64(c, 0)).collect();
+        let graph = CsrGraph::from_parts(nodes, decode(self.offsets), decode(self.targets))?;
+
+        let columns = self
+            .column_iter()
+            .map(|(name, values)| {
+                Ok(AttributeColumn {
+                    name: String::from_utf8(name.to_vec())
+                        .map_err(|_| corrupt("attribute column name is not UTF-8"))?,
+                    values: values.chunks_exact(8).map(|c| read_u64(c, 0) as i64).collect(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Snapshot { graph, columns })
+    }
+}
+
+/// A snapshot file mapped into memory.
+pub struct MappedSnapshot {
+    map: Mmap,
+    layout: Layout,
+}
+
+impl MappedSnapshot {
+    /// Maps the file and validates it once up front.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+        // Safety: the snapshot is treated as read-only and is not expected to
+        // be modified while mapped; a concurrent writer can at worst make us
+        // read garbage, which the checksum and bounds checks catch at open.
+        let map = unsafe { Mmap::map(&file)? };
+        let layout = Layout::parse(&map)?;
+        Ok(Self { map, layout })
+    }
+
+    pub fn get(&self) -> SnapshotRef<'_> {
+        self.layout.view(&self.map)
+    }
+}
+
+fn corrupt(message: &str) -> Error {
+    Error::InvalidData(message.to_owned())
+}
+
+/// Number of zero bytes needed to pad `len` up to a multiple of 8.
+fn padding(len: usize) -> usize {
+    (8 - len % 8) % 8
+}
+
+fn read_u64(bytes: &[u8], at: usize) -> u64 {
+    let mut buf = [0; 8];
+    buf.copy_from_slice(&bytes[at..at + 8]);
+    u64::from_le_bytes(buf)
+}
+
+/// Forwards writes while hashing everything that goes through.
+struct ChecksumWriter<W: Write> {
+    inner: W,
+    hasher: crc32fast::Hasher,
+}
+
+impl<W: Write> ChecksumWriter<W> {
+    fn write_u64(&mut self, value: u64) -> std::io::Result<()> {
+        self.write_all(&value.to_le_bytes())
+    }
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hw1::parse_nodes;
+
+    fn example() -> crate::Result<Snapshot> {
+        const NODES: &[u8] = b"id	status	gender	major	dorm	year
+1	1	1	265	0	2008
+2	5	2	265	0	2006
+3	1	1	0	0	2009";
+        let network = SimpleNetwork::from_edges(vec![1, 2, 3, 4], [(1, 2), (2, 3)])?;
+        Ok(Snapshot::from_network(&network).with_node_data(&parse_nodes(NODES)?))
+    }
+
+    #[test]
+    fn round_trip() -> crate::Result<()> {
+        let snapshot = example()?;
+        let mut bytes = vec![];
+        snapshot.write(&mut bytes)?;
+
+        let reloaded = Snapshot::read(bytes.as_slice())?;
+        assert_eq!(reloaded, snapshot);
+        assert_eq!(reloaded.column("year").map(|c| c.values.clone()), Some(vec![2008, 2006, 2009, 0]));
+        assert_eq!(reloaded.to_network()?.num_edges(), 2);
+
+        let borrowed = SnapshotRef::parse(&bytes)?;
+        assert_eq!(borrowed.neighbors(1).collect::<Vec<_>>(), vec![0, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_corruption_and_other_versions() -> crate::Result<()> {
+        let mut bytes = vec![];
+        example()?.write(&mut bytes)?;
+
+        let mut corrupted = bytes.clone();
+        corrupted[HEADER_LEN] ^= 1;
+        assert!(Snapshot::read(corrupted.as_slice()).is_err());
+
+        let mut future = bytes.clone();
+        future[8] = 2;
+        assert!(Snapshot::read(future.as_slice()).is_err());
+
+        // a target past the last node, with the checksum fixed up to match
+        let mut dangling = bytes.clone();
+        let first_target = HEADER_LEN + 4 * 8 + 5 * 8;
+        dangling[first_target] = 99;
+        let body = dangling.len() - 8;
+        let checksum = crc32fast::hash(&dangling[..body]) as u64;
+        dangling[body..].copy_from_slice(&checksum.to_le_bytes());
+        assert!(matches!(SnapshotRef::parse(&dangling), Err(Error::InvalidData(_))));
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+//! Centralities from the adjacency matrix: eigenvector, Katz and PageRank.
+//!
+//! All three are fixed points found by iterating a matrix-vector product
+//! over the CSR adjacency until no entry moves by more than
+//! [`Convergence::tolerance`]. Eigenvector centrality iterates `A + I`,
+//! which has the same eigenvectors as `A` but a dominant eigenvalue even on
+//! bipartite graphs, where plain power iteration would oscillate. Katz
+//! centrality `x = alpha A x + 1` only has a positive solution for `alpha`
+//! below `1 / lambda_max`, so `alpha` is checked against the leading
+//! eigenvalue first.
+
+use crate::csr::CsrGraph;
+use crate::error::{Error, Result};
+use crate::node::Node;
+
+/// When to stop an iteration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Convergence {
+    /// Largest change in any entry between iterations that counts as
+    /// converged
+    pub tolerance: f64,
+    /// Iterations before giving up with [`Error::NoConvergence`]
+    pub max_iterations: usize,
+}
+
+impl Default for Convergence {
+    fn default() -> Self {
+        Self { tolerance: 1e-10, max_iterations: 1000 }
+    }
+}
+
+impl Convergence {
+    /// Applies `step` to `x` until it converges, returning the number of
+    /// iterations taken
+    fn iterate<F: FnMut(&[f64]) -> Vec<f64>>(&self, x: &mut Vec<f64>, mut step: F) -> Result<usize> {
+        for iteration in 1..=self.max_iterations {
+            let next = step(x);
+            let change = next.iter().zip(x.iter()).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+            *x = next;
+            if change < self.tolerance {
+                return Ok(iteration);
+            }
+        }
+        Err(Error::NoConvergence { iterations: self.max_iterations })
+    }
+}
+
+/// `A x`
+fn multiply<N: Node>(graph: &CsrGraph<N>, x: &[f64]) -> Vec<f64> {
+    (0..graph.num_nodes()).map(|node| graph.neighbors(node).iter().map(|&nbr| x[nbr]).sum()).collect()
+}
+
+fn normalize(x: &mut [f64]) {
+    let norm = x.iter().map(|v| v * v).sum::<f64>().sqrt();
+    if norm > 0.0 {
+        x.iter_mut().for_each(|v| *v /= norm);
+    }
+}
+
+/// The leading eigenvalue of the adjacency matrix and its eigenvector, with
+/// unit Euclidean norm and nonnegative entries, by node index.
+///
+/// On a disconnected graph the vector is zero outside the component(s) with
+/// the largest eigenvalue.
+pub fn leading_eigenvector<N: Node>(graph: &CsrGraph<N>, convergence: Convergence) -> Result<(f64, Vec<f64>)> {
+    let n = graph.num_nodes();
+    let mut x = vec![1.0 / (n as f64).sqrt(); n];
+    convergence.iterate(&mut x, |x| {
+        let mut next = multiply(graph, x);
+        next.iter_mut().zip(x).for_each(|(a, b)| *a += b);
+        normalize(&mut next);
+        next
+    })?;
+    // Rayleigh quotient
+    let eigenvalue = multiply(graph, &x).iter().zip(&x).map(|(a, b)| a * b).sum();
+    Ok((eigenvalue, x))
+}
+
+/// Katz centrality `x = alpha A x + 1` of each node index, for
+/// `0 < alpha < 1 / lambda_max`.
+pub fn katz<N: Node>(graph: &CsrGraph<N>, alpha: f64, convergence: Convergence) -> Result<Vec<f64>> {
+    let (eigenvalue, _) = leading_eigenvector(graph, convergence)?;
+    if !(alpha > 0.0 && alpha * eigenvalue < 1.0) {
+        return Err(Error::InvalidParameter(format!(
+            "Katz alpha must be in (0, 1/lambda_max) = (0, {}), got {}",
+            1.0 / eigenvalue,
+            alpha
+        )));
+    }
+    let mut x = vec![1.0; graph.num_nodes()];
+    convergence.iterate(&mut x, |x| multiply(graph, x).into_iter().map(|ax| alpha * ax + 1.0).collect())?;
+    Ok(x)
+}
+
+/// PageRank of each node index, summing to 1.
+///
+/// A walker follows a random edge with probability `damping` and otherwise
+/// jumps to a node drawn from `personalization` (one finite, nonnegative
+/// weight per node index; uniform if `None`). Walkers at isolated nodes,
+/// which have no edge to follow, always jump.
+pub fn pagerank<N: Node>(graph: &CsrGraph<N>, damping: f64, personalization: Option<&[f64]>, convergence: Convergence) -> Result<Vec<f64>> {
+    let n = graph.num_nodes();
+    if !(0.0..1.0).contains(&damping) {
+        return Err(Error::InvalidParameter(format!("PageRank damping must be in [0, 1), got {}", damping)));
+    }
+    let jump: Vec<f64> = match personalization {
+        Some(weights) => {
+            if weights.len() != n {
+                return Err(Error::InvalidParameter(format!(
+                    "personalization has {} weights for {} nodes",
+                    weights.len(),
+                    n
+                )));
+            }
+            let total: f64 = weights.iter().sum();
+            if !weights.iter().all(|&w| w.is_finite() && w >= 0.0) || total <= 0.0 {
+                return Err(Error::InvalidParameter("personalization weights must be nonnegative and not all zero".to_owned()));
+            }
+            weights.iter().map(|w| w / total).collect()
+        }
+        None => vec![1.0 / n as f64; n],
+    };
+    let mut x = jump.clone();
+    convergence.iterate(&mut x, |x| {
+        let share: Vec<f64> = (0..n).map(|node| x[node] / graph.degree(node).max(1) as f64).collect();
+        let dangling: f64 = (0..n).filter(|&node| graph.degree(node) == 0).map(|node| x[node]).sum();
+        let teleport = 1.0 - damping + damping * dangling;
+        multiply(graph, &share).into_iter().zip(&jump).map(|(walk, p)| damping * walk + teleport * p).collect()
+    })?;
+    Ok(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::analysis::Analysis;
+    use crate::csr::CsrGraph;
+    use crate::error::Error;
+    use crate::spectral::{self, Convergence};
+    use crate::SimpleNetwork;
+
+    #[test]
+    fn star_centralities() -> crate::Result<()> {
+        // a star with centre 0 and four leaves, plus an isolated node 5
+        let network = SimpleNetwork::from_edges((0..6u64).collect(), (1..5).map(|leaf| (0, leaf)))?;
+        let convergence = Convergence::default();
+
+        // lambda_max = 2, with eigenvector (2, 1, 1, 1, 1) / sqrt(8)
+        assert!((network.leading_eigenvalue(convergence)? - 2.0).abs() < 1e-8);
+        let eigenvector = network.eigenvector_centrality(convergence)?;
+        assert!((eigenvector[&0] - 2.0 / 8f64.sqrt()).abs() < 1e-8);
+        assert!((eigenvector[&1] - 1.0 / 8f64.sqrt()).abs() < 1e-8);
+        assert!(eigenvector[&5] < 1e-8);
+
+        // x_0 = 1 + 4 alpha x_leaf and x_leaf = 1 + alpha x_0
+        let katz = network.katz_centrality(0.25, convergence)?;
+        assert!((katz[&0] - 2.0 / 0.75).abs() < 1e-8 && katz[&5] == 1.0);
+        assert!(network.katz_centrality(0.5, convergence).is_err());
+
+        let pagerank = network.pagerank(0.85, None, convergence)?;
+        assert!((pagerank.values().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(pagerank[&0] > pagerank[&1] && pagerank[&1] > pagerank[&5]);
+        // every walk restarts at the isolated node, and cannot leave it
+        let personal = HashMap::from([(5, 1.0)]);
+        let pagerank = network.pagerank(0.5, Some(&personal), convergence)?;
+        assert!((pagerank[&5] - 1.0).abs() < 1e-9);
+        assert!(network.pagerank(1.0, None, convergence).is_err());
+        // one finite, nonnegative weight per node
+        let graph = CsrGraph::from_network(&network);
+        for weights in [vec![1.0; 5], vec![1.0, 1.0, 1.0, 1.0, 1.0, f64::NAN], vec![1.0, 1.0, 1.0, 1.0, 1.0, f64::INFINITY]] {
+            assert!(matches!(spectral::pagerank(&graph, 0.85, Some(&weights), convergence), Err(Error::InvalidParameter(_))));
+        }
+        Ok(())
+    }
+}
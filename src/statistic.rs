@@ -4,14 +4,14 @@ use rand::seq::SliceRandom;
 use rand::Rng;
 use std::collections::HashMap;
 use std::collections::HashSet;
-use crate::SimpleNetwork;
-use std::time::Instant;
+use crate::analysis::Analysis;
+use crate::graph::Graph;
 
-pub fn remove_attrs(nodes: Vec<AttrNode>, alpha: f64) -> Vec<AttrNode> {
+pub fn remove_attrs<R: Rng + ?Sized>(nodes: Vec<AttrNode>, alpha: f64, rng: &mut R) -> Vec<AttrNode> {
 	let num_observed = ((nodes.len() as f64)*alpha) as usize;
 	
 	let mut numbers: Vec<usize> = (0..nodes.len()).collect();
- 	numbers.shuffle(&mut rand::thread_rng());
+ 	numbers.shuffle(rng);
  	let usable_numbers = &numbers[0..num_observed];
  	
  	let temp = nodes.clone();
@@ -26,25 +26,22 @@ pub fn remove_attrs(nodes: Vec<AttrNode>, alpha: f64) -> Vec<AttrNode> {
 	
 }
 
-pub fn infer_attrs(network: SimpleNetwork<AttrNode>, baseline: Vec<i32>) -> Vec<AttrNode> {
-	let mut guessed_nodes: Vec<AttrNode> = network.nodes.clone().iter().map(|&x| x).collect();
+pub fn infer_attrs<G: Graph<Node = AttrNode>, R: Rng + ?Sized>(network: &G, baseline: Vec<i32>, rng: &mut R) -> Vec<AttrNode> {
+	let mut guessed_nodes: Vec<AttrNode> = network.nodes().collect();
 	
 	for node in &mut guessed_nodes {
 		if node.attr == -1 {
-			if let Some(neighbors) = network.get_adjs(*node){
-				let mut counts: HashMap<i32,usize> = HashMap::new();
-				neighbors.iter().map(|&x| x.attr).filter(|&x| x >= 0).for_each(|x| {*counts.entry(x).or_default() += 1;});
-				
-				//take most common, break ties randomly
-				if counts.len() > 0 {
-					let max_cnt = counts.iter().max_by(|a, b| a.1.cmp(&b.1)).map(|(_, v)| v);
-					if max_cnt.is_some() {
-						let guesses: Vec<i32> = counts.iter().filter(|(_,&v)|  v == *max_cnt.unwrap()).map(|(&k, _)| k).collect();
-						node.attr = guesses[rand::thread_rng().gen_range(0..guesses.len())]
-					}
-				} else {
-					node.attr = baseline[rand::thread_rng().gen_range(0..baseline.len())];
-				}
+			let mut counts: HashMap<i32,usize> = HashMap::new();
+			network.neighbors(*node).map(|x| x.attr).filter(|&x| x >= 0).for_each(|x| {*counts.entry(x).or_default() += 1;});
+			
+			//take most common, break ties randomly
+			if let Some(&max_cnt) = counts.values().max() {
+				//sorted, so that the same draw picks the same guess in every run
+				let mut guesses: Vec<i32> = counts.iter().filter(|(_,&v)| v == max_cnt).map(|(&k, _)| k).collect();
+				guesses.sort();
+				node.attr = guesses[rng.gen_range(0..guesses.len())]
+			} else {
+				node.attr = baseline[rng.gen_range(0..baseline.len())];
 			}
 		}
 	}
@@ -52,70 +49,64 @@ pub fn infer_attrs(network: SimpleNetwork<AttrNode>, baseline: Vec<i32>) -> Vec<
 	guessed_nodes
 }
 
-pub fn remove_edges(edges: Vec<Edge>, alpha: f64) -> Vec<Edge> {
+pub fn remove_edges<R: Rng + ?Sized>(edges: Vec<Edge>, alpha: f64, rng: &mut R) -> Vec<Edge> {
 	let num_observed = ((edges.len() as f64)*alpha) as usize;
 	
 	let mut numbers: Vec<usize> = (0..edges.len()).collect();
- 	numbers.shuffle(&mut rand::thread_rng());
+ 	numbers.shuffle(rng);
  	let usable_numbers = &numbers[0..num_observed];
  	
  	let temp = edges.clone();
- 	let new_edges = temp.iter().enumerate().filter(|(i,_)| usable_numbers.contains(&i))
+ 	let new_edges = temp.iter().enumerate().filter(|(i,_)| usable_numbers.contains(i))
  											.map(|(_,edge)| *edge);
 	new_edges.collect()
 	
 }
 
 
-pub fn jaccard_scores(network: SimpleNetwork<AttrNode>) -> HashMap<(AttrNode, AttrNode), f64> {
-	let pairs = network.get_nonedges();
+pub fn jaccard_scores<G: Graph<Node = AttrNode>, R: Rng + ?Sized>(network: &G, rng: &mut R) -> HashMap<(AttrNode, AttrNode), f64> {
+	let pairs = sorted_nonedges(network);
+	let neighborhoods: HashMap<AttrNode,HashSet<AttrNode>> = network.nodes().map(|node| (node,network.neighbors(node).collect())).collect();
 	let mut scores = HashMap::new();
 	for (src,dst) in pairs {
-		//dbg!("doing a pair");
-		if let Some(src_nbrs) = network.get_adjs(src){
-			if let Some(dst_nbrs) = network.get_adjs(dst){
-				let union_size = src_nbrs.union(dst_nbrs).collect::<HashSet<_>>().len();
-				let inter_size = src_nbrs.intersection(dst_nbrs).collect::<HashSet<_>>().len();
-				//random noise - 1/n will never reorder scores
-				let noise = rand::thread_rng().gen::<f64>()/(network.nodes.len() as f64);
-				if union_size > 0 {
-					scores.insert((src,dst),(inter_size as f64)/(union_size as f64) + noise);
-				} else {
-					scores.insert((src,dst),noise);
-				}
-			}
+		let src_nbrs = &neighborhoods[&src];
+		let dst_nbrs = &neighborhoods[&dst];
+		let inter_size = src_nbrs.intersection(dst_nbrs).count();
+		let union_size = src_nbrs.len() + dst_nbrs.len() - inter_size;
+		//random noise - 1/n will never reorder scores
+		let noise = rng.gen::<f64>()/(network.num_nodes() as f64);
+		if union_size > 0 {
+			scores.insert((src,dst),(inter_size as f64)/(union_size as f64) + noise);
+		} else {
+			scores.insert((src,dst),noise);
 		}
 	}
 	scores
 }
 
 
-pub fn dp_scores(network: SimpleNetwork<AttrNode>) -> HashMap<(AttrNode, AttrNode), f64> {
-	let pairs = network.get_nonedges();
+pub fn dp_scores<G: Graph<Node = AttrNode>, R: Rng + ?Sized>(network: &G, rng: &mut R) -> HashMap<(AttrNode, AttrNode), f64> {
+	let pairs = sorted_nonedges(network);
 	let mut scores = HashMap::new();
 	for (src,dst) in pairs {
-		if let Some(src_nbrs) = network.get_adjs(src){
-			if let Some(dst_nbrs) = network.get_adjs(dst){
-				let src_k = src_nbrs.len();
-				let dst_k = dst_nbrs.len();
-				//random noise - 1/2 will never reorder scores
-				let noise = rand::thread_rng().gen::<f64>()/2.0;
-				scores.insert((src,dst),(src_k*dst_k) as f64 + noise);
-			}
-		}
+		let src_k = network.degree(src);
+		let dst_k = network.degree(dst);
+		//random noise - 1/2 will never reorder scores
+		let noise = rng.gen::<f64>()/2.0;
+		scores.insert((src,dst),(src_k*dst_k) as f64 + noise);
 	}
 	scores
 }
 
-pub fn sp_scores(network: SimpleNetwork<AttrNode>) -> HashMap<(AttrNode, AttrNode), f64> {
-	let pairs = network.get_nonedges();
+pub fn sp_scores<G: Graph<Node = AttrNode>, R: Rng + ?Sized>(network: &G, rng: &mut R) -> HashMap<(AttrNode, AttrNode), f64> {
+	let pairs = sorted_nonedges(network);
 	//precompute apsp
 	let paths = network.apsp();
 	
 	let mut scores = HashMap::new();
 	for (src,dst) in pairs {
 		//random noise - 1/n will never reorder scores
-		let noise = rand::thread_rng().gen::<f64>()/(network.nodes.len() as f64);
+		let noise = rng.gen::<f64>()/(network.num_nodes() as f64);
 		let path_length = paths[src.node_id as usize][dst.node_id as usize];
 		if path_length >= 0{
 			scores.insert((src,dst),1.0/(path_length as f64) + noise);
@@ -126,6 +117,15 @@ pub fn sp_scores(network: SimpleNetwork<AttrNode>) -> HashMap<(AttrNode, AttrNod
 	scores
 }
 
+/// The non-edges of `network`, sorted so that each pair draws the same noise
+/// in every run
+fn sorted_nonedges<G: Graph<Node = AttrNode>>(network: &G) -> Vec<(AttrNode, AttrNode)> {
+	let mut pairs: Vec<(AttrNode, AttrNode)> = network.get_nonedges().into_iter().collect();
+	pairs.sort();
+	pairs
+}
+
+#[allow(non_snake_case, clippy::needless_range_loop)]
 pub fn roc(scores: HashMap<(AttrNode, AttrNode), f64>, edges: HashSet<(AttrNode,AttrNode)>) -> Vec<(f64,f64)> {
 	let mut scores_aug = vec!();
 	let mut curve = vec!();
@@ -142,7 +142,7 @@ pub fn roc(scores: HashMap<(AttrNode, AttrNode), f64>, edges: HashSet<(AttrNode,
 		}
 	}
 	//dbg!(split.elapsed());
-	scores_aug.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+	scores_aug.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then(a.0.cmp(&b.0)));
 	let mut running_T = 0;
 	let mut running_F = 0;
 	curve.push((0.0,0.0));
@@ -161,7 +161,27 @@ pub fn roc(scores: HashMap<(AttrNode, AttrNode), f64>, edges: HashSet<(AttrNode,
 	curve
 }
 
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::SimpleNetwork;
+	use rand::SeedableRng;
+	use rand_chacha::ChaCha8Rng;
 
+	#[test]
+	fn seeded_runs_agree() -> crate::Result<()> {
+		//two triangles joined by an edge; node 5's neighbours are split between attributes 1 and 2
+		let nodes: Vec<AttrNode> = [1, 1, 1, 2, 2, -1].iter().enumerate().map(|(i,&attr)| AttrNode {node_id: i as u64, attr}).collect();
+		let pairs = [(0,1),(1,2),(2,0),(3,4),(2,3),(5,0),(5,3)];
+		let network = SimpleNetwork::from_edges(nodes.clone(), pairs.iter().map(|&(a,b)| (nodes[a],nodes[b])))?;
 
-
-
+		let run = |seed: u64| {
+			let mut rng = ChaCha8Rng::seed_from_u64(seed);
+			let inferred = infer_attrs(&network, vec![1,2], &mut rng);
+			let scores = [jaccard_scores(&network, &mut rng), dp_scores(&network, &mut rng), sp_scores(&network, &mut rng)];
+			(inferred, scores)
+		};
+		assert_eq!(run(7), run(7));
+		Ok(())
+	}
+}
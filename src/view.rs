@@ -0,0 +1,165 @@
+//! Read-only views over an existing [`Graph`].
+//!
+//! A view borrows the graph it is built on and filters what it reports, so
+//! making one costs only the node or edge set that defines it; no adjacency
+//! is copied. Views implement [`Graph`] themselves, so every analysis runs
+//! on them directly, and they can be stacked (an ego network of an
+//! edge-masked graph, say).
+
+use std::collections::{HashSet, VecDeque};
+
+use crate::graph::Graph;
+
+/// The subgraph made of a set of nodes and all edges between them.
+#[derive(Debug, Clone)]
+pub struct InducedSubgraph<'a, G: Graph> {
+    graph: &'a G,
+    nodes: HashSet<G::Node>,
+}
+
+impl<'a, G: Graph> InducedSubgraph<'a, G> {
+    /// Keeps `nodes`; those not in `graph` are ignored.
+    pub fn new<I: IntoIterator<Item = G::Node>>(graph: &'a G, nodes: I) -> Self {
+        let nodes = nodes.into_iter().filter(|&node| graph.has_node(node)).collect();
+        Self { graph, nodes }
+    }
+}
+
+impl<G: Graph> Graph for InducedSubgraph<'_, G> {
+    type Node = G::Node;
+
+    fn nodes(&self) -> impl Iterator<Item = G::Node> + '_ {
+        self.nodes.iter().copied()
+    }
+
+    fn neighbors(&self, node: G::Node) -> impl Iterator<Item = G::Node> + '_ {
+        self.has_node(node)
+            .then(|| self.graph.neighbors(node).filter(|nbr| self.nodes.contains(nbr)))
+            .into_iter()
+            .flatten()
+    }
+
+    fn has_node(&self, node: G::Node) -> bool {
+        self.nodes.contains(&node)
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn has_edge(&self, from: G::Node, to: G::Node) -> bool {
+        self.has_node(from) && self.has_node(to) && self.graph.has_edge(from, to)
+    }
+}
+
+/// A graph with some of its edges hidden, e.g. the held-out edges of a link
+/// prediction experiment. All nodes are kept.
+#[derive(Debug, Clone)]
+pub struct EdgeMask<'a, G: Graph> {
+    graph: &'a G,
+    /// Hidden edges, each stored once with the smaller node first
+    hidden: HashSet<(G::Node, G::Node)>,
+}
+
+impl<'a, G: Graph> EdgeMask<'a, G> {
+    /// Hides `edges`, given in either direction; pairs that are not edges of
+    /// `graph` are ignored.
+    pub fn new<I: IntoIterator<Item = (G::Node, G::Node)>>(graph: &'a G, edges: I) -> Self {
+        let hidden = edges
+            .into_iter()
+            .filter(|&(u, v)| graph.has_edge(u, v))
+            .map(|(u, v)| (u.min(v), u.max(v)))
+            .collect();
+        Self { graph, hidden }
+    }
+
+    /// The hidden edges, smaller node first
+    pub fn hidden(&self) -> &HashSet<(G::Node, G::Node)> {
+        &self.hidden
+    }
+
+    fn is_hidden(&self, u: G::Node, v: G::Node) -> bool {
+        self.hidden.contains(&(u.min(v), u.max(v)))
+    }
+}
+
+impl<G: Graph> Graph for EdgeMask<'_, G> {
+    type Node = G::Node;
+
+    fn nodes(&self) -> impl Iterator<Item = G::Node> + '_ {
+        self.graph.nodes()
+    }
+
+    fn neighbors(&self, node: G::Node) -> impl Iterator<Item = G::Node> + '_ {
+        self.graph.neighbors(node).filter(move |&nbr| !self.is_hidden(node, nbr))
+    }
+
+    fn has_node(&self, node: G::Node) -> bool {
+        self.graph.has_node(node)
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.graph.num_nodes()
+    }
+
+    fn num_edges(&self) -> u64 {
+        self.graph.num_edges() - self.hidden.len() as u64
+    }
+
+    fn has_edge(&self, from: G::Node, to: G::Node) -> bool {
+        !self.is_hidden(from, to) && self.graph.has_edge(from, to)
+    }
+}
+
+/// The nodes within `radius` hops of `center` and the edges between them.
+///
+/// Empty if `center` is not in the graph.
+pub fn ego_network<G: Graph>(graph: &G, center: G::Node, radius: usize) -> InducedSubgraph<'_, G> {
+    let mut reached = HashSet::new();
+    let mut queue = VecDeque::new();
+    if graph.has_node(center) {
+        reached.insert(center);
+        queue.push_back((center, 0));
+    }
+    while let Some((node, dist)) = queue.pop_front() {
+        if dist == radius {
+            continue;
+        }
+        for nbr in graph.neighbors(node) {
+            if reached.insert(nbr) {
+                queue.push_back((nbr, dist + 1));
+            }
+        }
+    }
+    InducedSubgraph { graph, nodes: reached }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::Analysis;
+    use crate::SimpleNetwork;
+
+    #[test]
+    fn views_filter_without_copying() -> crate::Result<()> {
+        // path 0-1-2-3-4 with a chord 1-3
+        let network = SimpleNetwork::from_edges((0..5u64).collect(), [(0, 1), (1, 2), (2, 3), (3, 4), (1, 3)])?;
+
+        let sub = InducedSubgraph::new(&network, [1, 2, 3, 9]);
+        assert_eq!((sub.num_nodes(), sub.num_edges()), (3, 3));
+        assert!(!sub.has_edge(0, 1) && sub.neighbors(0).next().is_none());
+        assert_eq!(sub.cluster_coeff(), 1.0);
+
+        let masked = EdgeMask::new(&network, [(3, 1), (0, 4)]);
+        assert_eq!((masked.num_nodes(), masked.num_edges()), (5, 4));
+        assert!(!masked.has_edge(1, 3) && !masked.has_edge(3, 1));
+        assert_eq!(masked.mgd_diameter().1, 4);
+
+        let ego = ego_network(&masked, 0, 2);
+        let mut nodes: Vec<u64> = ego.nodes().collect();
+        nodes.sort();
+        assert_eq!(nodes, vec![0, 1, 2]);
+        assert_eq!(ego.num_edges(), 2);
+        Ok(())
+    }
+}